@@ -10,16 +10,19 @@ struct Options {
 fn main() -> anyhow::Result<()> {
     let options = Options::parse();
 
-    let data = std::fs::read(&options.input)?;
-    let decoded = flarmnet::tdb::decode_file(&data)?;
+    let decoded = flarmnet::tdb::decode_path(&options.input)?;
 
     println!("Version: {}", decoded.version);
-    println!("Records: {}", decoded.records.len());
 
-    let ok_count = decoded.records.iter().filter(|r| r.is_ok()).count();
-    let err_count = decoded.records.iter().filter(|r| r.is_err()).count();
-    println!("  OK: {}", ok_count);
-    println!("  Errors: {}", err_count);
+    let stats = decoded.stats();
+    println!("Records: {}", stats.records);
+    println!("  OK: {}", stats.ok);
+    println!("    without frequency: {}", stats.empty_frequency);
+    println!("    with registration: {}", stats.with_registration);
+    println!("  Errors: {}", stats.errors);
+    for (kind, count) in &stats.error_kinds {
+        println!("    {}: {}", kind, count);
+    }
 
     println!();
     println!("First 5 records:");
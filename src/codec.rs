@@ -0,0 +1,91 @@
+use crate::File;
+
+/// Decoding and encoding of a whole [File] in one of the file formats.
+///
+/// The `tdb`, `lx`, `xcsoar` and `csv` modules provide an implementation
+/// as their `Codec` type, e.g. `tdb::Codec`, so that conversions can be
+/// written once for all of these formats.
+///
+/// The `ogn` module has no `Codec`, since a [File] can not hold the
+/// `TRACKED`/`IDENTIFIED` privacy flags of the DDB. Decoding a DDB dump into
+/// a [File] and encoding it again would publish devices whose owners opted
+/// out, so the DDB is read and written as `ogn::Device`s instead.
+///
+/// # Examples
+///
+/// ```
+/// use flarmnet::FormatCodec;
+///
+/// fn convert<From: FormatCodec, To: FormatCodec>(
+///     data: &[u8],
+/// ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+///     let file = From::decode(data)?;
+///     Ok(To::encode(&file)?)
+/// }
+///
+/// let fln = include_bytes!("../tests/fixtures/data.fln");
+/// let tdb = convert::<flarmnet::xcsoar::Codec, flarmnet::tdb::Codec>(fln).unwrap();
+/// assert_eq!(flarmnet::tdb::decode_file(&tdb).unwrap().version, 28592);
+/// ```
+pub trait FormatCodec {
+    type DecodeError: std::error::Error + 'static;
+    type EncodeError: std::error::Error + 'static;
+
+    /// Decodes a file, failing with the error of the first record that can
+    /// not be decoded.
+    fn decode(data: &[u8]) -> Result<File, Self::DecodeError>;
+
+    /// Encodes a file.
+    fn encode(file: &File) -> Result<Vec<u8>, Self::EncodeError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "csv", feature = "lx", feature = "tdb", feature = "xcsoar"))]
+    fn round_trip<C: FormatCodec>() {
+        use crate::Record;
+
+        let file = File {
+            version: 1,
+            records: vec![Record {
+                flarm_id: "3EE3C7".to_string(),
+                pilot_name: "Tobias Bieniek".to_string(),
+                airfield: "EDKA".to_string(),
+                plane_type: "LS6a".to_string(),
+                registration: "D-0816".to_string(),
+                call_sign: "SG".to_string(),
+                frequency: "130.530".to_string(),
+                ..Default::default()
+            }],
+        };
+
+        let decoded = C::decode(&C::encode(&file).unwrap()).unwrap();
+        assert_eq!(decoded, file);
+    }
+
+    #[test]
+    #[cfg(feature = "csv")]
+    fn csv_codec_round_trips() {
+        round_trip::<crate::csv::Codec>();
+    }
+
+    #[test]
+    #[cfg(feature = "lx")]
+    fn lx_codec_round_trips() {
+        round_trip::<crate::lx::Codec>();
+    }
+
+    #[test]
+    #[cfg(feature = "tdb")]
+    fn tdb_codec_round_trips() {
+        round_trip::<crate::tdb::Codec>();
+    }
+
+    #[test]
+    #[cfg(feature = "xcsoar")]
+    fn xcsoar_codec_round_trips() {
+        round_trip::<crate::xcsoar::Codec>();
+    }
+}
@@ -0,0 +1,305 @@
+//! Import and export of records as CSV, e.g. for fleet lists maintained in
+//! spreadsheets.
+//!
+//! The columns are named after the [Record] fields:
+//!
+//! ```csv
+//! flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency
+//! 3EE3C7,Tobias Bieniek,EDKA,LS6a,D-0816,SG,130.530
+//! ```
+//!
+//! Values that contain commas, quotes or line breaks are enclosed in double
+//! quotes, with quotes escaped by doubling them.
+
+use crate::{File, Record};
+use std::io::{Read, Write};
+use thiserror::Error;
+
+const COLUMNS: [&str; 7] = [
+    "flarm_id",
+    "pilot_name",
+    "airfield",
+    "plane_type",
+    "registration",
+    "call_sign",
+    "frequency",
+];
+
+#[derive(Error, Debug)]
+pub enum CsvError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("missing column: {0}")]
+    MissingColumn(&'static str),
+    #[error("unterminated quote in line {line}")]
+    UnterminatedQuote { line: usize },
+    #[error("expected {expected} values in line {line}, found {found}")]
+    MissingValues {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Writes the records of a file as CSV, with a header row.
+///
+/// The file version is not written.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![Record {
+///         flarm_id: "3EE3C7".to_string(),
+///         pilot_name: "Bieniek, Tobias".to_string(),
+///         ..Default::default()
+///     }],
+/// };
+///
+/// let mut csv = Vec::new();
+/// flarmnet::csv::write_csv(&file, &mut csv).unwrap();
+/// assert_eq!(csv, b"flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency
+/// 3EE3C7,\"Bieniek, Tobias\",,,,,
+/// ");
+/// ```
+pub fn write_csv<W: Write>(file: &File, mut w: W) -> Result<(), CsvError> {
+    writeln!(w, "{}", COLUMNS.join(","))?;
+
+    for record in &file.records {
+        let values = COLUMNS.map(|column| quote(record.field(column).unwrap()));
+        writeln!(w, "{}", values.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Reads records from CSV with a header row into a [File] with version `1`.
+///
+/// The columns may be in any order, and unknown columns are ignored. Fails
+/// with [CsvError::MissingColumn] if one of the [Record] fields has no
+/// column.
+///
+/// # Examples
+///
+/// ```
+/// let csv = "registration,flarm_id,pilot_name,airfield,plane_type,call_sign,frequency,notes
+/// D-0816,3EE3C7,Tobias Bieniek,EDKA,LS6a,SG,130.530,club glider
+/// ";
+///
+/// let file = flarmnet::csv::read_csv(csv.as_bytes()).unwrap();
+/// assert_eq!(file.version, 1);
+/// assert_eq!(file.records[0].registration, "D-0816");
+/// assert_eq!(file.records[0].flarm_id, "3EE3C7");
+/// ```
+pub fn read_csv<R: Read>(mut r: R) -> Result<File, CsvError> {
+    let mut csv = String::new();
+    r.read_to_string(&mut csv)?;
+
+    let mut rows = parse_rows(&csv)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(CsvError::MissingColumn(COLUMNS[0]));
+    };
+
+    let mut positions = [0; COLUMNS.len()];
+    for (position, column) in positions.iter_mut().zip(COLUMNS) {
+        *position = header
+            .iter()
+            .position(|name| name.trim() == column)
+            .ok_or(CsvError::MissingColumn(column))?;
+    }
+    let expected = positions.iter().max().unwrap() + 1;
+
+    let records = rows
+        .map(|(line, mut values)| {
+            if values.len() < expected {
+                return Err(CsvError::MissingValues {
+                    line,
+                    expected,
+                    found: values.len(),
+                });
+            }
+
+            let [
+                flarm_id,
+                pilot_name,
+                airfield,
+                plane_type,
+                registration,
+                call_sign,
+                frequency,
+            ] = positions.map(|position| std::mem::take(&mut values[position]));
+
+            Ok(Record {
+                flarm_id,
+                pilot_name,
+                airfield,
+                plane_type,
+                registration,
+                call_sign,
+                frequency,
+                ..Default::default()
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(File {
+        version: 1,
+        records,
+    })
+}
+
+/// [FormatCodec](crate::FormatCodec) implementation for CSV, using
+/// [read_csv] and [write_csv]. The file version is not stored, and decoded
+/// files have version `1`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Codec;
+
+impl crate::FormatCodec for Codec {
+    type DecodeError = CsvError;
+    type EncodeError = CsvError;
+
+    fn decode(data: &[u8]) -> Result<File, CsvError> {
+        read_csv(data)
+    }
+
+    fn encode(file: &File) -> Result<Vec<u8>, CsvError> {
+        let mut csv = Vec::new();
+        write_csv(file, &mut csv)?;
+        Ok(csv)
+    }
+}
+
+// The quoting and parsing below stand in for the `csv` crate, which is not
+// available as a dependency yet. Only `read_csv` and `write_csv` are public,
+// so they can be switched over once it is.
+
+fn quote(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Splits CSV into rows of values, together with the 1-based line number
+/// each row starts on. Empty lines are skipped.
+fn parse_rows(csv: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut value = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut quoted = false;
+
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                value.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if value.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                value.push(c);
+            }
+            ',' if !quoted => row.push(std::mem::take(&mut value)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut value));
+                if row.len() > 1 || !row[0].is_empty() {
+                    rows.push((row_line, std::mem::take(&mut row)));
+                } else {
+                    row.clear();
+                }
+                line += 1;
+                row_line = line;
+            }
+            c => value.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(CsvError::UnterminatedQuote { line: row_line });
+    }
+    if !value.is_empty() || !row.is_empty() {
+        row.push(value);
+        rows.push((row_line, row));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn csv_round_trips() {
+        let file = File {
+            version: 1,
+            records: vec![
+                Record {
+                    flarm_id: "3EE3C7".to_string(),
+                    pilot_name: "Bieniek, \"Tobi\"".to_string(),
+                    airfield: "EDKA\nAachen".to_string(),
+                    plane_type: " LS6a ".to_string(),
+                    registration: "D-0816".to_string(),
+                    call_sign: "SG".to_string(),
+                    frequency: "130.530".to_string(),
+                    ..Default::default()
+                },
+                Record::default(),
+            ],
+        };
+
+        let mut csv = Vec::new();
+        write_csv(&file, &mut csv).unwrap();
+        assert_eq!(read_csv(csv.as_slice()).unwrap(), file);
+    }
+
+    #[test]
+    fn reading_accepts_crlf() {
+        let csv = "flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency\r\n\
+                   3EE3C7,,EDKA,,D-0816,,\r\n\
+                   \r\n";
+
+        let file = read_csv(csv.as_bytes()).unwrap();
+        assert_eq!(file.records.len(), 1);
+        assert_eq!(file.records[0].airfield, "EDKA");
+        assert_eq!(file.records[0].frequency, "");
+    }
+
+    #[test]
+    fn reading_fails_for_invalid_csv() {
+        let csv = "flarm_id,registration\n3EE3C7,D-0816\n";
+        assert_debug_snapshot!(read_csv(csv.as_bytes()).unwrap_err(), @r###"
+        MissingColumn(
+            "pilot_name",
+        )
+        "###);
+
+        let csv = "flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency\n\
+                   3EE3C7,\"Tobias,,,,,\n";
+        assert_debug_snapshot!(read_csv(csv.as_bytes()).unwrap_err(), @r###"
+        UnterminatedQuote {
+            line: 2,
+        }
+        "###);
+
+        let csv = "flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency\n\
+                   3EE3C7,,,,,,\n\
+                   000001,,\n";
+        assert_debug_snapshot!(read_csv(csv.as_bytes()).unwrap_err(), @r###"
+        MissingValues {
+            line: 3,
+            expected: 7,
+            found: 3,
+        }
+        "###);
+    }
+}
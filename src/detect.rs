@@ -0,0 +1,230 @@
+//! Detection of the file format of FlarmNet and related files, see
+//! [decode_any].
+
+#[cfg(feature = "csv")]
+use crate::File;
+use thiserror::Error;
+
+/// A file format that [detect_format] recognizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// See [tdb](crate::tdb).
+    #[cfg(feature = "tdb")]
+    Tdb,
+    /// See [lx](crate::lx).
+    #[cfg(feature = "lx")]
+    Lx,
+    /// See [xcsoar](crate::xcsoar).
+    #[cfg(feature = "xcsoar")]
+    Xcsoar,
+    /// See [csv](crate::csv).
+    #[cfg(feature = "csv")]
+    Csv,
+    /// See [ogn](crate::ogn).
+    #[cfg(feature = "ogn")]
+    Ogn,
+}
+
+/// A file decoded by [decode_any], tagged with its format.
+#[derive(Debug)]
+pub enum DetectedFile {
+    #[cfg(feature = "tdb")]
+    Tdb(crate::tdb::DecodedFile),
+    #[cfg(feature = "lx")]
+    Lx(crate::lx::DecodedFile),
+    #[cfg(feature = "xcsoar")]
+    Xcsoar(crate::xcsoar::DecodedFile),
+    #[cfg(feature = "csv")]
+    Csv(File),
+    #[cfg(feature = "ogn")]
+    Ogn(crate::ogn::DecodedFile),
+}
+
+impl DetectedFile {
+    /// Returns the format that the file was decoded from.
+    pub fn format(&self) -> Format {
+        match self {
+            #[cfg(feature = "tdb")]
+            DetectedFile::Tdb(_) => Format::Tdb,
+            #[cfg(feature = "lx")]
+            DetectedFile::Lx(_) => Format::Lx,
+            #[cfg(feature = "xcsoar")]
+            DetectedFile::Xcsoar(_) => Format::Xcsoar,
+            #[cfg(feature = "csv")]
+            DetectedFile::Csv(_) => Format::Csv,
+            #[cfg(feature = "ogn")]
+            DetectedFile::Ogn(_) => Format::Ogn,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum DetectError {
+    #[error("unknown file format")]
+    UnknownFormat,
+    /// A text format contains invalid UTF-8.
+    #[error("invalid UTF-8")]
+    InvalidUtf8,
+    #[cfg(feature = "tdb")]
+    #[error(transparent)]
+    Tdb(#[from] crate::tdb::DecodeError),
+    #[cfg(feature = "lx")]
+    #[error(transparent)]
+    Lx(#[from] crate::lx::DecodeError),
+    #[cfg(feature = "xcsoar")]
+    #[error(transparent)]
+    Xcsoar(#[from] crate::xcsoar::DecodeError),
+    #[cfg(feature = "csv")]
+    #[error(transparent)]
+    Csv(#[from] crate::csv::CsvError),
+}
+
+/// Returns the format of the file, or `None` if it is not recognized.
+///
+/// Only the start of the file is inspected:
+///
+/// - TDB files start with the TDB magic bytes.
+/// - LX files start with an (encrypted) `<`.
+/// - OGN DDB dumps start with their `#DEVICE_TYPE` header or a quoted value.
+/// - CSV files start with a header row that has a `flarm_id` column.
+/// - XCSoar files start with a line of hex digits holding the version.
+///
+/// A leading UTF-8 byte order mark is ignored for the text formats.
+pub fn detect_format(data: &[u8]) -> Option<Format> {
+    #[cfg(feature = "tdb")]
+    if data.starts_with(&crate::tdb::MAGIC) {
+        return Some(Format::Tdb);
+    }
+
+    #[cfg(feature = "lx")]
+    if strip_bom(data).first() == Some(&(b'<' + 1)) {
+        return Some(Format::Lx);
+    }
+
+    #[cfg(feature = "ogn")]
+    if first_line(data).starts_with(b"#DEVICE_TYPE") || first_line(data).starts_with(b"'") {
+        return Some(Format::Ogn);
+    }
+
+    #[cfg(feature = "csv")]
+    if first_line(data)
+        .split(|&b| b == b',')
+        .any(|column| column.trim_ascii() == b"flarm_id")
+    {
+        return Some(Format::Csv);
+    }
+
+    #[cfg(feature = "xcsoar")]
+    if !first_line(data).is_empty() && first_line(data).iter().all(u8::is_ascii_hexdigit) {
+        return Some(Format::Xcsoar);
+    }
+
+    None
+}
+
+/// Decodes a file in any of the supported formats, as detected by
+/// [detect_format].
+///
+/// Fails with [DetectError::UnknownFormat] if the format is not
+/// recognized, and with the error of the decoder otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use flarmnet::{DetectedFile, Format};
+///
+/// let data = include_bytes!("../tests/fixtures/data.fln");
+///
+/// let decoded = flarmnet::decode_any(data).unwrap();
+/// assert_eq!(decoded.format(), Format::Xcsoar);
+/// let DetectedFile::Xcsoar(file) = decoded else { unreachable!() };
+/// assert_eq!(file.version, 28592);
+///
+/// assert!(flarmnet::decode_any(b"FlarmNet").is_err());
+/// ```
+pub fn decode_any(data: &[u8]) -> Result<DetectedFile, DetectError> {
+    let format = detect_format(data).ok_or(DetectError::UnknownFormat)?;
+
+    Ok(match format {
+        #[cfg(feature = "tdb")]
+        Format::Tdb => DetectedFile::Tdb(crate::tdb::decode_file(data)?),
+        #[cfg(feature = "lx")]
+        Format::Lx => DetectedFile::Lx(crate::lx::decode_file(strip_bom(data))?),
+        #[cfg(feature = "xcsoar")]
+        Format::Xcsoar => {
+            let text = std::str::from_utf8(data).map_err(|_| DetectError::InvalidUtf8)?;
+            DetectedFile::Xcsoar(crate::xcsoar::decode_file(text)?)
+        }
+        #[cfg(feature = "csv")]
+        Format::Csv => DetectedFile::Csv(crate::csv::read_csv(strip_bom(data))?),
+        #[cfg(feature = "ogn")]
+        Format::Ogn => {
+            let text = std::str::from_utf8(data).map_err(|_| DetectError::InvalidUtf8)?;
+            DetectedFile::Ogn(crate::ogn::decode_file(text))
+        }
+    })
+}
+
+#[cfg(any(feature = "csv", feature = "lx", feature = "ogn", feature = "xcsoar"))]
+fn strip_bom(data: &[u8]) -> &[u8] {
+    data.strip_prefix("\u{FEFF}".as_bytes()).unwrap_or(data)
+}
+
+/// Returns the first line, without a byte order mark and the line ending.
+#[cfg(any(feature = "csv", feature = "ogn", feature = "xcsoar"))]
+fn first_line(data: &[u8]) -> &[u8] {
+    let line = strip_bom(data)
+        .split(|&b| b == b'\n')
+        .next()
+        .unwrap_or_default();
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "lx", feature = "tdb", feature = "xcsoar"))]
+    fn detecting_fixtures_works() {
+        let fixtures: [(&[u8], Format); 3] = [
+            (
+                include_bytes!("../tests/fixtures/flarmnet.tdb"),
+                Format::Tdb,
+            ),
+            (include_bytes!("../tests/fixtures/lx.fln"), Format::Lx),
+            (include_bytes!("../tests/fixtures/data.fln"), Format::Xcsoar),
+        ];
+
+        for (data, format) in fixtures {
+            assert_eq!(detect_format(data), Some(format));
+            assert_eq!(decode_any(data).unwrap().format(), format);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "csv", feature = "ogn"))]
+    fn detecting_csv_formats_works() {
+        let csv = "\u{FEFF}flarm_id,pilot_name,airfield,plane_type,registration,call_sign,frequency\r\n\
+                   3EE3C7,,EDKA,LS6a,D-0816,SG,130.530\r\n";
+        let DetectedFile::Csv(file) = decode_any(csv.as_bytes()).unwrap() else {
+            panic!("expected CSV");
+        };
+        assert_eq!(file.records[0].registration, "D-0816");
+
+        let ddb = "#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED\n\
+                   'F','3EE3C7','LS-6','D-0816','SG','Y','Y'\n";
+        let DetectedFile::Ogn(file) = decode_any(ddb.as_bytes()).unwrap() else {
+            panic!("expected OGN DDB");
+        };
+        assert_eq!(file.devices.len(), 1);
+    }
+
+    #[test]
+    fn detecting_fails_for_unknown_formats() {
+        for data in [&b""[..], b"\n006fb0", b"FlarmNet", b"\xff\xfe"] {
+            assert_eq!(detect_format(data), None);
+            assert!(matches!(decode_any(data), Err(DetectError::UnknownFormat)));
+        }
+    }
+}
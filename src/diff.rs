@@ -0,0 +1,402 @@
+use crate::{File, Record};
+use std::collections::BTreeMap;
+
+/// Differences between two [File]s, matching records by FLARM id.
+///
+/// FLARM ids are matched as described for [join]. If a file contains the
+/// same FLARM id more than once, the last record wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff<'a> {
+    /// Records that only exist in the new file.
+    pub added: Vec<&'a Record>,
+    /// Records that only exist in the old file.
+    pub removed: Vec<&'a Record>,
+    /// Records that exist in both files but differ, as `(old, new)` pairs.
+    pub changed: Vec<(&'a Record, &'a Record)>,
+}
+
+impl FileDiff<'_> {
+    /// Returns `true` if the two files contain the same records.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Renders the record counts as a human-readable summary line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let old = File { version: 1, records: vec![] };
+    /// let new = File {
+    ///     version: 2,
+    ///     records: vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }],
+    /// };
+    ///
+    /// assert_eq!(old.diff(&new).summary(), "added 1, removed 0, changed 0");
+    /// ```
+    pub fn summary(&self) -> String {
+        format!(
+            "added {}, removed {}, changed {}",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// A record of a known-good reference file that got lost or lost data, see
+/// [File::regressions_against].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regression<'a> {
+    /// The reference record has no counterpart with the same FLARM id.
+    Missing(&'a Record),
+    /// Fields that are filled in the reference record are empty in the
+    /// edited record.
+    ClearedFields {
+        reference: &'a Record,
+        record: &'a Record,
+        fields: Vec<&'static str>,
+    },
+}
+
+impl File {
+    /// Lists the records of a known-good `reference` file that are missing
+    /// from this file, or whose previously filled fields are now empty.
+    ///
+    /// Unlike [File::diff], added records and changed values are not
+    /// reported, since they don't indicate data loss. Records are matched as
+    /// described for [join], and the regressions are sorted by FLARM id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record, Regression};
+    /// let reference = File {
+    ///     version: 1,
+    ///     records: vec![Record {
+    ///         flarm_id: "3EE3C7".to_string(),
+    ///         pilot_name: "Tobias Bieniek".to_string(),
+    ///         ..Default::default()
+    ///     }],
+    /// };
+    /// let mut edited = reference.clone();
+    /// edited.records[0].pilot_name.clear();
+    ///
+    /// assert_eq!(edited.regressions_against(&reference), vec![Regression::ClearedFields {
+    ///     reference: &reference.records[0],
+    ///     record: &edited.records[0],
+    ///     fields: vec!["pilot_name"],
+    /// }]);
+    /// ```
+    pub fn regressions_against<'a>(&'a self, reference: &'a File) -> Vec<Regression<'a>> {
+        join(reference, self)
+            .filter_map(|pair| match pair {
+                (Some(reference), None) => Some(Regression::Missing(reference)),
+                (Some(reference), Some(record)) => {
+                    let fields = cleared_fields(reference, record);
+                    (!fields.is_empty()).then_some(Regression::ClearedFields {
+                        reference,
+                        record,
+                        fields,
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Compares this file against a `new` version of it.
+    ///
+    /// The records in each list of the resulting [FileDiff] are sorted by
+    /// FLARM id.
+    pub fn diff<'a>(&'a self, new: &'a File) -> FileDiff<'a> {
+        let mut diff = FileDiff {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+
+        for pair in join(self, new) {
+            match pair {
+                (Some(old_record), None) => diff.removed.push(old_record),
+                (None, Some(new_record)) => diff.added.push(new_record),
+                (Some(old_record), Some(new_record)) if new_record != old_record => {
+                    diff.changed.push((old_record, new_record))
+                }
+                _ => {}
+            }
+        }
+
+        diff
+    }
+}
+
+/// Pairs the records of two files by FLARM id, as a full outer join.
+///
+/// The pairs are sorted by FLARM id. A side is `None` if the FLARM id only
+/// exists in the other file. FLARM ids are compared as hex numbers, so that
+/// `"00000A"` matches `"a"`. Ids that are not valid FLARM ids are compared
+/// case-insensitively and sorted after all valid ids. If a file contains the
+/// same FLARM id more than once, the last record wins.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let record = |id: &str| Record { flarm_id: id.to_string(), ..Default::default() };
+/// let a = File { version: 1, records: vec![record("000002"), record("000001")] };
+/// let b = File { version: 2, records: vec![record("000003"), record("000002")] };
+///
+/// let pairs = flarmnet::join(&a, &b).collect::<Vec<_>>();
+/// assert_eq!(pairs, [
+///     (Some(&a.records[1]), None),
+///     (Some(&a.records[0]), Some(&b.records[1])),
+///     (None, Some(&b.records[0])),
+/// ]);
+/// ```
+pub fn join<'a>(
+    a: &'a File,
+    b: &'a File,
+) -> impl Iterator<Item = (Option<&'a Record>, Option<&'a Record>)> {
+    let mut pairs: BTreeMap<FlarmIdKey, (Option<&Record>, Option<&Record>)> = by_flarm_id(a)
+        .into_iter()
+        .map(|(flarm_id, record)| (flarm_id, (Some(record), None)))
+        .collect();
+
+    for (flarm_id, record) in by_flarm_id(b) {
+        pairs.entry(flarm_id).or_default().1 = Some(record);
+    }
+
+    pairs.into_values()
+}
+
+fn cleared_fields(reference: &Record, record: &Record) -> Vec<&'static str> {
+    [
+        ("pilot_name", &reference.pilot_name, &record.pilot_name),
+        ("airfield", &reference.airfield, &record.airfield),
+        ("plane_type", &reference.plane_type, &record.plane_type),
+        (
+            "registration",
+            &reference.registration,
+            &record.registration,
+        ),
+        ("call_sign", &reference.call_sign, &record.call_sign),
+        ("frequency", &reference.frequency, &record.frequency),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| !before.trim().is_empty() && after.trim().is_empty())
+    .map(|(field, _, _)| field)
+    .collect()
+}
+
+fn by_flarm_id(file: &File) -> BTreeMap<FlarmIdKey, &Record> {
+    file.records
+        .iter()
+        .map(|record| (FlarmIdKey::new(&record.flarm_id), record))
+        .collect()
+}
+
+/// The FLARM id that records of different files are matched by.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum FlarmIdKey {
+    Valid(u32),
+    /// The uppercased id, if it is not a 24-bit hex number.
+    Invalid(String),
+}
+
+impl FlarmIdKey {
+    pub(crate) fn new(flarm_id: &str) -> Self {
+        match u32::from_str_radix(flarm_id, 16) {
+            Ok(id) if id <= 0xFFFFFF => FlarmIdKey::Valid(id),
+            _ => FlarmIdKey::Invalid(flarm_id.to_ascii_uppercase()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{File, Record, Regression};
+
+    fn make_record(flarm_id: &str, registration: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_works() {
+        let old = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "D-0001"),
+                make_record("000002", "D-0002"),
+                make_record("000003", "D-0003"),
+            ],
+        };
+        let new = File {
+            version: 2,
+            records: vec![
+                make_record("000004", "D-0004"),
+                make_record("000003", "D-0003"),
+                make_record("000002", "D-2222"),
+            ],
+        };
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![&new.records[0]]);
+        assert_eq!(diff.removed, vec![&old.records[0]]);
+        assert_eq!(diff.changed, vec![(&old.records[1], &new.records[2])]);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.summary(), "added 1, removed 1, changed 1");
+    }
+
+    #[test]
+    fn diff_ignores_flarm_id_case() {
+        let old = File {
+            version: 1,
+            records: vec![make_record("c0ffee", "D-0001")],
+        };
+        let mut new = old.clone();
+        new.records[0].flarm_id = "C0FFEE".to_string();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.changed.len(), 1);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "tdb")]
+    fn diff_ignores_record_positions() {
+        use crate::tdb::{DecodeOptions, decode_file_with, encode_file};
+
+        let decode = |file: &File| {
+            let options = DecodeOptions::builder().record_positions(true).build();
+            let data = encode_file(file).unwrap();
+            let decoded = decode_file_with(&data, &options).unwrap();
+            File {
+                version: decoded.version,
+                records: decoded.records.into_iter().map(Result::unwrap).collect(),
+            }
+        };
+
+        let old = File {
+            version: 1,
+            records: vec![
+                make_record("000002", "D-0002"),
+                make_record("000003", "D-0003"),
+            ],
+        };
+        let mut new = old.clone();
+        new.records.insert(0, make_record("000001", "D-0001"));
+
+        let (old, new) = (decode(&old), decode(&new));
+        assert_ne!(old.records[0].position, new.records[1].position);
+        assert_eq!(old.diff(&new).summary(), "added 1, removed 0, changed 0");
+    }
+
+    #[test]
+    fn diff_compares_flarm_ids_as_numbers() {
+        let old = File {
+            version: 1,
+            records: vec![
+                make_record("00000A", "D-0001"),
+                make_record("1", "D-0002"),
+                make_record("xyz", "D-0003"),
+            ],
+        };
+        let new = File {
+            version: 2,
+            records: vec![
+                make_record("a", "D-0001"),
+                make_record("000001", "D-2222"),
+                make_record("XYZ", "D-0003"),
+                make_record("1000001", "D-0004"),
+            ],
+        };
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec![&new.records[3]]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(
+            diff.changed,
+            vec![
+                (&old.records[1], &new.records[1]),
+                (&old.records[0], &new.records[0]),
+                (&old.records[2], &new.records[2]),
+            ]
+        );
+        assert!(new.regressions_against(&old).is_empty());
+    }
+
+    #[test]
+    fn join_pairs_records_by_flarm_id() {
+        let a = File {
+            version: 1,
+            records: vec![
+                make_record("c0ffee", "D-0001"),
+                make_record("000001", "D-0002"),
+            ],
+        };
+        let b = File {
+            version: 2,
+            records: vec![make_record("C0FFEE", "D-1111")],
+        };
+
+        let pairs = crate::join(&a, &b).collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                (Some(&a.records[1]), None),
+                (Some(&a.records[0]), Some(&b.records[0])),
+            ]
+        );
+        assert_eq!(
+            crate::join(
+                &b,
+                &File {
+                    version: 0,
+                    records: vec![]
+                }
+            )
+            .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn regressions_against_reports_lost_data() {
+        let reference = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "D-0001"),
+                make_record("000002", "D-0002"),
+                make_record("000003", "D-0003"),
+            ],
+        };
+        let edited = File {
+            version: 2,
+            records: vec![
+                make_record("000004", ""),
+                make_record("000003", "D-3333"),
+                make_record("000002", " "),
+            ],
+        };
+
+        assert_eq!(
+            edited.regressions_against(&reference),
+            [
+                Regression::Missing(&reference.records[0]),
+                Regression::ClearedFields {
+                    reference: &reference.records[1],
+                    record: &edited.records[2],
+                    fields: vec!["registration"],
+                },
+            ]
+        );
+        assert!(reference.regressions_against(&reference).is_empty());
+    }
+}
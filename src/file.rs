@@ -0,0 +1,759 @@
+use crate::diff::FlarmIdKey;
+use crate::frequency::parse_khz;
+use crate::{File, Record};
+use std::collections::{BTreeMap, HashMap};
+
+impl File {
+    /// Returns the index of every record that is missing a critical field,
+    /// together with the names of the empty fields (`flarm_id`,
+    /// `registration`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let record = Record {
+    ///     flarm_id: "3EE3C7".to_string(),
+    ///     pilot_name: "Tobias Bieniek".to_string(),
+    ///     airfield: "EDKA".to_string(),
+    ///     plane_type: "LS6a".to_string(),
+    ///     registration: "".to_string(),
+    ///     call_sign: "SG".to_string(),
+    ///     frequency: "130.530".to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let file = File { version: 1, records: vec![record] };
+    ///
+    /// assert_eq!(file.incomplete(), vec![(0, vec!["registration"])]);
+    /// ```
+    pub fn incomplete(&self) -> Vec<(usize, Vec<&'static str>)> {
+        self.records
+            .iter()
+            .enumerate()
+            .filter_map(|(i, record)| {
+                let mut missing = Vec::new();
+                if record.flarm_id.trim().is_empty() {
+                    missing.push("flarm_id");
+                }
+                if record.registration.trim().is_empty() {
+                    missing.push("registration");
+                }
+                (!missing.is_empty()).then_some((i, missing))
+            })
+            .collect()
+    }
+
+    /// Returns the index of every record whose frequency is outside of the
+    /// inclusive `min_mhz..=max_mhz` range.
+    ///
+    /// Records without a frequency are ignored, while records with a
+    /// frequency that can not be parsed are reported as being out of range.
+    /// Frequencies are parsed like the encoders do, in MHz with up to three
+    /// decimals, and the bounds are rounded to kHz.
+    pub fn frequencies_within(&self, min_mhz: f64, max_mhz: f64) -> Vec<usize> {
+        let range = (min_mhz * 1000.0).round()..=(max_mhz * 1000.0).round();
+        self.records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| !record.frequency.is_empty())
+            .filter(|(_, record)| match parse_khz(&record.frequency, false) {
+                Some(khz) => !range.contains(&f64::from(khz)),
+                None => true,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns the records whose FLARM id is within `start..end`, sorted by
+    /// FLARM id.
+    ///
+    /// Records with an invalid FLARM id are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let file = File {
+    ///     version: 1,
+    ///     records: ["3EE3C7", "000010", "00000F"]
+    ///         .iter()
+    ///         .map(|id| Record { flarm_id: id.to_string(), ..Default::default() })
+    ///         .collect(),
+    /// };
+    ///
+    /// let records = file.records_in_range("000000", "000010").unwrap();
+    /// assert_eq!(records, vec![&file.records[2]]);
+    /// ```
+    #[cfg(feature = "tdb")]
+    pub fn records_in_range(
+        &self,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<&Record>, crate::tdb::EncodeError> {
+        use crate::tdb::parse_flarm_id;
+
+        let range = parse_flarm_id(start)?..parse_flarm_id(end)?;
+
+        let mut records: Vec<(u32, &Record)> = self
+            .records
+            .iter()
+            .filter_map(|record| Some((parse_flarm_id(&record.flarm_id).ok()?, record)))
+            .filter(|(id, _)| range.contains(id))
+            .collect();
+        records.sort_by_key(|(id, _)| *id);
+
+        Ok(records.into_iter().map(|(_, record)| record).collect())
+    }
+
+    /// Returns the records sorted by FLARM id, as the TDB encoder stores them.
+    ///
+    /// Records with the same FLARM id keep their order, and records with an
+    /// invalid FLARM id come last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let file: File = ["3EE3C7", "00000F", "000010"]
+    ///     .iter()
+    ///     .map(|id| Record { flarm_id: id.to_string(), ..Default::default() })
+    ///     .collect();
+    ///
+    /// let ids: Vec<_> = file.sorted_by_flarm_id().iter().map(|r| &r.flarm_id).collect();
+    /// assert_eq!(ids, ["00000F", "000010", "3EE3C7"]);
+    /// ```
+    pub fn sorted_by_flarm_id(&self) -> Vec<&Record> {
+        let mut records: Vec<&Record> = self.records.iter().collect();
+        records.sort_by_key(|&record| {
+            u32::from_str_radix(&record.flarm_id, 16).map_err(|_| record.flarm_id.as_str())
+        });
+        records
+    }
+
+    /// Returns the records in the order of the file they were decoded from,
+    /// according to [Record::position].
+    ///
+    /// Records without a position come last, in their current order. This
+    /// allows keeping e.g. exports in a stable order, although editing and
+    /// re-encoding a TDB file sorts its records by FLARM id.
+    pub fn in_original_order(&self) -> Vec<&Record> {
+        let mut records: Vec<&Record> = self.records.iter().collect();
+        records.sort_by_key(|record| record.position.unwrap_or(usize::MAX));
+        records
+    }
+
+    /// Removes trailing placeholder records with a zero FLARM id and no other
+    /// data, as found in files padded to a fixed capacity, and returns how
+    /// many records were removed.
+    pub fn trim_trailing_empty(&mut self) -> usize {
+        let len = self.records.len();
+        let keep = self
+            .records
+            .iter()
+            .rposition(|record| !is_empty_placeholder(record))
+            .map_or(0, |i| i + 1);
+
+        self.records.truncate(keep);
+        len - keep
+    }
+
+    /// Merges records that share the same FLARM id into a single record.
+    ///
+    /// Unlike a last-wins replacement, each field of the merged record takes
+    /// the first non-empty value found among the duplicates, in file order.
+    /// The merged record takes the place of the first duplicate. FLARM ids
+    /// are compared as described for [join](crate::join).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let mut file = File {
+    ///     version: 1,
+    ///     records: vec![
+    ///         Record {
+    ///             flarm_id: "3EE3C7".to_string(),
+    ///             frequency: "130.530".to_string(),
+    ///             ..Default::default()
+    ///         },
+    ///         Record {
+    ///             flarm_id: "3ee3c7".to_string(),
+    ///             pilot_name: "Tobias Bieniek".to_string(),
+    ///             frequency: "123.500".to_string(),
+    ///             ..Default::default()
+    ///         },
+    ///     ],
+    /// };
+    ///
+    /// file.merge_fields();
+    /// assert_eq!(file.records.len(), 1);
+    /// assert_eq!(file.records[0].pilot_name, "Tobias Bieniek");
+    /// assert_eq!(file.records[0].frequency, "130.530");
+    /// ```
+    pub fn merge_fields(&mut self) {
+        let mut positions: HashMap<FlarmIdKey, usize> = HashMap::new();
+        let mut merged: Vec<Record> = Vec::with_capacity(self.records.len());
+
+        for record in self.records.drain(..) {
+            let key = FlarmIdKey::new(&record.flarm_id);
+            match positions.get(&key) {
+                Some(&i) => fill_empty_fields(&mut merged[i], record),
+                None => {
+                    positions.insert(key, merged.len());
+                    merged.push(record);
+                }
+            }
+        }
+
+        self.records = merged;
+    }
+
+    /// Groups records that only differ in their frequency, and returns each
+    /// group with its distinct frequencies.
+    ///
+    /// The first record of each group is returned, and both the groups and
+    /// their frequencies are in the order of their first occurrence. Empty
+    /// frequencies are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let record = |frequency: &str| Record {
+    ///     flarm_id: "3EE3C7".to_string(),
+    ///     frequency: frequency.to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let file = File { version: 1, records: vec![record("123.500"), record("130.530")] };
+    ///
+    /// let collapsed = file.collapse_frequencies();
+    /// assert_eq!(collapsed, vec![(record("123.500"), vec!["123.500".to_string(), "130.530".to_string()])]);
+    /// ```
+    pub fn collapse_frequencies(&self) -> Vec<(Record, Vec<String>)> {
+        let mut positions: HashMap<Record, usize> = HashMap::new();
+        let mut groups: Vec<(Record, Vec<String>)> = Vec::new();
+
+        for record in &self.records {
+            let key = Record {
+                frequency: String::new(),
+                ..record.clone()
+            };
+            let i = *positions.entry(key).or_insert_with(|| {
+                groups.push((record.clone(), Vec::new()));
+                groups.len() - 1
+            });
+
+            let frequencies = &mut groups[i].1;
+            if !record.frequency.is_empty() && !frequencies.contains(&record.frequency) {
+                frequencies.push(record.frequency.clone());
+            }
+        }
+
+        groups
+    }
+
+    /// Splits the file into one file per registration country prefix, e.g.
+    /// `D` for `D-0816`.
+    ///
+    /// The prefix is the part of the registration before the first hyphen,
+    /// and is uppercased. Records without a hyphen in their registration are
+    /// grouped under an empty prefix. Every file keeps the original version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{File, Record};
+    /// let record = |registration: &str| Record { registration: registration.to_string(), ..Default::default() };
+    /// let file = File { version: 1, records: vec![record("D-0816"), record("OE-5678"), record("d-kesh")] };
+    ///
+    /// let countries = file.split_by_country();
+    /// assert_eq!(countries.keys().collect::<Vec<_>>(), ["D", "OE"]);
+    /// assert_eq!(countries["D"].records.len(), 2);
+    /// ```
+    pub fn split_by_country(&self) -> BTreeMap<String, File> {
+        let mut files: BTreeMap<String, File> = BTreeMap::new();
+        for record in &self.records {
+            let prefix = match record.registration.trim().split_once('-') {
+                Some((prefix, _)) => prefix.trim().to_uppercase(),
+                None => String::new(),
+            };
+
+            files
+                .entry(prefix)
+                .or_insert_with(|| File {
+                    version: self.version,
+                    records: Vec::new(),
+                })
+                .records
+                .push(record.clone());
+        }
+
+        files
+    }
+
+    /// Adds a record with the given FLARM id and `"UNKNOWN"` as registration
+    /// and plane type, unless the file already contains the FLARM id, and
+    /// returns whether the record was added.
+    ///
+    /// See [File::ensure_placeholder_with] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::File;
+    /// let mut file = File { version: 1, records: vec![] };
+    ///
+    /// assert!(file.ensure_placeholder("000000"));
+    /// assert!(!file.ensure_placeholder("000000"));
+    /// assert_eq!(file.records[0].registration, "UNKNOWN");
+    /// ```
+    pub fn ensure_placeholder(&mut self, flarm_id: &str) -> bool {
+        self.ensure_placeholder_with(flarm_id, "UNKNOWN")
+    }
+
+    /// Adds a record with the given FLARM id and `placeholder` as
+    /// registration and plane type, unless the file already contains the
+    /// FLARM id, and returns whether the record was added.
+    ///
+    /// FLARM ids are compared case-insensitively. The record is appended to
+    /// the end of the file.
+    pub fn ensure_placeholder_with(&mut self, flarm_id: &str, placeholder: &str) -> bool {
+        let exists = self
+            .records
+            .iter()
+            .any(|record| record.flarm_id.eq_ignore_ascii_case(flarm_id));
+        if exists {
+            return false;
+        }
+
+        self.records.push(Record {
+            flarm_id: flarm_id.to_string(),
+            plane_type: placeholder.to_string(),
+            registration: placeholder.to_string(),
+            ..Default::default()
+        });
+        true
+    }
+
+    /// Calls [Record::anonymize] on every record.
+    pub fn anonymize(&mut self) {
+        self.records.iter_mut().for_each(Record::anonymize);
+    }
+
+    /// Calls [Record::anonymize_with] on every record.
+    pub fn anonymize_with(&mut self, blank_registration: bool) {
+        for record in &mut self.records {
+            record.anonymize_with(blank_registration);
+        }
+    }
+}
+
+/// Creates a file with version `0` from the given records.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let records = vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }];
+///
+/// let file: File = records.into();
+/// assert_eq!(file.version, 0);
+/// assert_eq!(file.records.len(), 1);
+/// ```
+impl From<Vec<Record>> for File {
+    fn from(records: Vec<Record>) -> Self {
+        File {
+            version: 0,
+            records,
+        }
+    }
+}
+
+/// Collects records into a file with version `0`.
+impl FromIterator<Record> for File {
+    fn from_iter<I: IntoIterator<Item = Record>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+fn fill_empty_fields(target: &mut Record, source: Record) {
+    let fields = [
+        (&mut target.pilot_name, source.pilot_name),
+        (&mut target.airfield, source.airfield),
+        (&mut target.plane_type, source.plane_type),
+        (&mut target.registration, source.registration),
+        (&mut target.call_sign, source.call_sign),
+        (&mut target.frequency, source.frequency),
+    ];
+    for (target, source) in fields {
+        if target.is_empty() {
+            *target = source;
+        }
+    }
+
+    if target.address_type == 0 {
+        target.address_type = source.address_type;
+    }
+}
+
+fn is_empty_placeholder(record: &Record) -> bool {
+    record.flarm_id.trim().chars().all(|c| c == '0')
+        && record.pilot_name.is_empty()
+        && record.airfield.is_empty()
+        && record.plane_type.is_empty()
+        && record.registration.is_empty()
+        && record.call_sign.is_empty()
+        && record.frequency.is_empty()
+        && record.address_type == 0
+        && record.reserved == [0; 8]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{File, Record};
+    use insta::assert_debug_snapshot;
+
+    fn make_record(flarm_id: &str, registration: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn incomplete_lists_missing_critical_fields() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("3EE3C7", "D-0816"),
+                make_record("", "D-2188"),
+                make_record("000001", " "),
+                make_record("", ""),
+            ],
+        };
+        assert_debug_snapshot!(file.incomplete(), @r###"
+        [
+            (
+                1,
+                [
+                    "flarm_id",
+                ],
+            ),
+            (
+                2,
+                [
+                    "registration",
+                ],
+            ),
+            (
+                3,
+                [
+                    "flarm_id",
+                    "registration",
+                ],
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn frequencies_within_lists_out_of_range_records() {
+        let frequencies = [
+            "123.500", "", "118.000", "137.000", "121.500", "abc", "inf", "NaN", "1.3e2", "136.975",
+        ];
+        let records = frequencies
+            .iter()
+            .map(|frequency| Record {
+                frequency: frequency.to_string(),
+                ..make_record("000001", "D-0816")
+            })
+            .collect();
+        let file = File {
+            version: 1,
+            records,
+        };
+        assert_eq!(
+            file.frequencies_within(122.0, 137.0),
+            vec![2, 4, 5, 6, 7, 8]
+        );
+        assert_eq!(
+            file.frequencies_within(122.0, 136.975),
+            vec![2, 3, 4, 5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn anonymize_scrubs_all_records() {
+        let mut file = File {
+            version: 1,
+            records: vec![
+                Record {
+                    pilot_name: "John Doe".to_string(),
+                    ..make_record("3EE3C7", "D-0816")
+                },
+                Record {
+                    pilot_name: "Jane Doe".to_string(),
+                    ..make_record("000001", "D-2188")
+                },
+            ],
+        };
+
+        file.anonymize();
+        assert!(file.records.iter().all(|r| r.pilot_name.is_empty()));
+        assert!(file.records.iter().all(|r| !r.registration.is_empty()));
+
+        file.anonymize_with(true);
+        assert!(file.records.iter().all(|r| r.registration.is_empty()));
+    }
+
+    #[test]
+    #[cfg(feature = "tdb")]
+    fn records_in_range_works() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("000020", "D-0020"),
+                make_record("00000f", "D-000F"),
+                make_record("ZZZZZZ", "D-ZZZZ"),
+                make_record("000010", "D-0010"),
+                make_record("000001", "D-0001"),
+            ],
+        };
+
+        let records = file.records_in_range("000001", "000020").unwrap();
+        let registrations: Vec<&str> = records.iter().map(|r| r.registration.as_str()).collect();
+        assert_eq!(registrations, vec!["D-0001", "D-000F", "D-0010"]);
+
+        assert_debug_snapshot!(file.records_in_range("000001", "foo").unwrap_err(), @r###"
+        InvalidFlarmId {
+            value: "foo",
+            index: None,
+        }
+        "###);
+    }
+
+    #[test]
+    fn trim_trailing_empty_removes_placeholders() {
+        let mut file = File {
+            version: 1,
+            records: vec![
+                make_record("000000", ""),
+                make_record("3EE3C7", "D-0816"),
+                make_record("000000", ""),
+                make_record("", ""),
+                make_record("000000", ""),
+            ],
+        };
+
+        assert_eq!(file.trim_trailing_empty(), 3);
+        assert_eq!(file.records.len(), 2);
+        assert_eq!(file.trim_trailing_empty(), 0);
+
+        file.records.remove(1);
+        assert_eq!(file.trim_trailing_empty(), 1);
+        assert!(file.records.is_empty());
+    }
+
+    #[test]
+    fn merge_fields_compares_flarm_ids_as_numbers() {
+        let mut file = File {
+            version: 1,
+            records: vec![
+                make_record("00000A", ""),
+                make_record("a", "D-2188"),
+                make_record("xyz", "D-0816"),
+                make_record("XYZ", "D-9999"),
+            ],
+        };
+
+        file.merge_fields();
+        assert_eq!(
+            file.records,
+            [
+                make_record("00000A", "D-2188"),
+                make_record("xyz", "D-0816")
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_fields_combines_duplicates() {
+        let mut file = File {
+            version: 1,
+            records: vec![
+                make_record("3EE3C7", ""),
+                make_record("000001", "D-2188"),
+                Record {
+                    frequency: "130.530".to_string(),
+                    ..make_record("3ee3c7", "D-0816")
+                },
+                Record {
+                    pilot_name: "Tobias Bieniek".to_string(),
+                    frequency: "123.500".to_string(),
+                    ..make_record("3EE3C7", "D-9999")
+                },
+            ],
+        };
+
+        file.merge_fields();
+        assert_debug_snapshot!(file.records, @r###"
+        [
+            Record {
+                flarm_id: "3EE3C7",
+                pilot_name: "Tobias Bieniek",
+                airfield: "",
+                plane_type: "",
+                registration: "D-0816",
+                call_sign: "",
+                frequency: "130.530",
+                address_type: 0,
+                reserved: [
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ],
+                position: None,
+            },
+            Record {
+                flarm_id: "000001",
+                pilot_name: "",
+                airfield: "",
+                plane_type: "",
+                registration: "D-2188",
+                call_sign: "",
+                frequency: "",
+                address_type: 0,
+                reserved: [
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ],
+                position: None,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn collecting_records_works() {
+        let file: File = ["3EE3C7", "000001"]
+            .into_iter()
+            .map(|id| make_record(id, ""))
+            .collect();
+
+        assert_eq!(file.version, 0);
+        assert_eq!(file.records.len(), 2);
+        assert_eq!(file.records[1].flarm_id, "000001");
+    }
+
+    #[test]
+    fn collapse_frequencies_groups_records() {
+        let record = |flarm_id: &str, frequency: &str| Record {
+            frequency: frequency.to_string(),
+            ..make_record(flarm_id, "D-0816")
+        };
+        let file = File {
+            version: 1,
+            records: vec![
+                record("3EE3C7", "123.500"),
+                record("000001", ""),
+                record("3EE3C7", "130.530"),
+                record("3EE3C7", "123.500"),
+                record("3EE3C7", ""),
+            ],
+        };
+
+        let collapsed = file.collapse_frequencies();
+        assert_eq!(
+            collapsed,
+            [
+                (
+                    file.records[0].clone(),
+                    vec!["123.500".into(), "130.530".into()]
+                ),
+                (file.records[1].clone(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn ensure_placeholder_adds_missing_ids() {
+        let mut file = File {
+            version: 1,
+            records: vec![make_record("3EE3C7", "D-0816")],
+        };
+
+        assert!(!file.ensure_placeholder("3ee3c7"));
+        assert!(file.ensure_placeholder_with("FFFFFF", "?"));
+        assert!(!file.ensure_placeholder("FFFFFF"));
+        assert_eq!(
+            file.records,
+            vec![
+                make_record("3EE3C7", "D-0816"),
+                Record {
+                    plane_type: "?".to_string(),
+                    ..make_record("FFFFFF", "?")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn file_round_trips_through_json() {
+        let file = File {
+            version: 1,
+            records: vec![
+                Record {
+                    pilot_name: "Tobias Bieniek".to_string(),
+                    address_type: 2,
+                    ..make_record("3EE3C7", "D-0816")
+                },
+                make_record("000001", ""),
+            ],
+        };
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert_eq!(serde_json::from_str::<File>(&json).unwrap(), file);
+    }
+
+    #[test]
+    fn split_by_country_groups_by_prefix() {
+        let file = File {
+            version: 42,
+            records: vec![
+                make_record("000001", "D-0816"),
+                make_record("000002", "HB-1234"),
+                make_record("000003", "N12345"),
+                make_record("000004", ""),
+                make_record("000005", " d-KESH"),
+            ],
+        };
+
+        let countries = file.split_by_country();
+        assert_eq!(countries.keys().collect::<Vec<_>>(), ["", "D", "HB"]);
+        assert!(countries.values().all(|file| file.version == 42));
+
+        let ids = |prefix: &str| {
+            countries[prefix]
+                .records
+                .iter()
+                .map(|record| record.flarm_id.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(""), ["000003", "000004"]);
+        assert_eq!(ids("D"), ["000001", "000005"]);
+        assert_eq!(ids("HB"), ["000002"]);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::Record;
+
+/// A coarse classification of radio frequencies, see
+/// [Record::frequency_band].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrequencyBand {
+    /// The 121.500 MHz emergency frequency.
+    Emergency,
+    /// The 122 and 123 MHz blocks, which include most frequencies used by
+    /// gliding sites and for air-to-air communication between gliders.
+    Gliding,
+    /// Any other frequency of the 118.000–136.975 MHz aeronautical VHF band.
+    Aeronautical,
+    /// A frequency outside of the aeronautical VHF band.
+    Other,
+}
+
+impl FrequencyBand {
+    /// Classifies a frequency given in kHz.
+    pub fn from_khz(khz: u32) -> Self {
+        match khz {
+            121_500 => FrequencyBand::Emergency,
+            122_000..=123_999 => FrequencyBand::Gliding,
+            118_000..=136_975 => FrequencyBand::Aeronautical,
+            _ => FrequencyBand::Other,
+        }
+    }
+}
+
+impl Record {
+    /// Classifies the frequency of the record into a [FrequencyBand].
+    ///
+    /// Returns `None` if the record has no frequency, or if it can not be
+    /// parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::{FrequencyBand, Record};
+    /// let record = Record { frequency: "123.500".to_string(), ..Default::default() };
+    /// assert_eq!(record.frequency_band(), Some(FrequencyBand::Gliding));
+    ///
+    /// let record = Record { frequency: "".to_string(), ..Default::default() };
+    /// assert_eq!(record.frequency_band(), None);
+    /// ```
+    pub fn frequency_band(&self) -> Option<FrequencyBand> {
+        parse_khz(self.frequency.trim(), false).map(FrequencyBand::from_khz)
+    }
+}
+
+/// Parses a frequency in MHz with up to three decimals, or in kHz if `raw`
+/// is set, into kHz.
+///
+/// The integer and fractional parts are parsed separately, so that values
+/// like `"123.985"` are not affected by floating point rounding.
+pub(crate) fn parse_khz(s: &str, raw: bool) -> Option<u32> {
+    if raw {
+        return s.parse().ok();
+    }
+
+    let (mhz, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let is_numeric = mhz
+        .bytes()
+        .chain(fraction.bytes())
+        .all(|b| b.is_ascii_digit());
+    if mhz.is_empty() || fraction.len() > 3 || !is_numeric {
+        return None;
+    }
+
+    let mhz: u32 = mhz.parse().ok()?;
+    let khz = (0..3)
+        .map(|i| {
+            fraction
+                .as_bytes()
+                .get(i)
+                .map_or(0, |b| u32::from(b - b'0'))
+        })
+        .fold(0, |khz, digit| khz * 10 + digit);
+    mhz.checked_mul(1000)?.checked_add(khz)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FrequencyBand, Record};
+    use alloc::string::ToString;
+
+    fn band(frequency: &str) -> Option<FrequencyBand> {
+        let record = Record {
+            frequency: frequency.to_string(),
+            ..Default::default()
+        };
+        record.frequency_band()
+    }
+
+    #[test]
+    fn frequency_band_works() {
+        assert_eq!(band("121.5"), Some(FrequencyBand::Emergency));
+        assert_eq!(band("122.475"), Some(FrequencyBand::Gliding));
+        assert_eq!(band("123.150"), Some(FrequencyBand::Gliding));
+        assert_eq!(band("130.530"), Some(FrequencyBand::Aeronautical));
+        assert_eq!(band("118.000"), Some(FrequencyBand::Aeronautical));
+        assert_eq!(band("137.000"), Some(FrequencyBand::Other));
+        assert_eq!(band("0.000"), Some(FrequencyBand::Other));
+        assert_eq!(band("abc"), None);
+        assert_eq!(band("-1"), None);
+        assert_eq!(band("122.4999"), None);
+        assert_eq!(band("123.999"), Some(FrequencyBand::Gliding));
+        assert_eq!(band(" 136.975 "), Some(FrequencyBand::Aeronautical));
+        assert_eq!(band("136.980"), Some(FrequencyBand::Other));
+    }
+}
@@ -1,11 +1,83 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// the tests use std, e.g. through the insta macros, even without the feature
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[cfg(feature = "csv")]
+pub mod csv;
 #[cfg(feature = "lx")]
 pub mod lx;
+#[cfg(feature = "ogn")]
+pub mod ogn;
 #[cfg(feature = "tdb")]
 pub mod tdb;
+#[cfg(feature = "serde")]
+pub mod web;
 #[cfg(feature = "xcsoar")]
 pub mod xcsoar;
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg(feature = "std")]
+mod codec;
+#[cfg(any(
+    feature = "csv",
+    feature = "lx",
+    feature = "ogn",
+    all(feature = "tdb", feature = "std"),
+    feature = "xcsoar"
+))]
+mod detect;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod file;
+mod frequency;
+#[cfg(all(
+    feature = "log",
+    any(feature = "lx", feature = "tdb", feature = "xcsoar")
+))]
+mod logging;
+#[cfg(feature = "std")]
+mod merge;
+mod profile;
+mod record;
+#[cfg(all(feature = "tdb", feature = "xcsoar"))]
+mod roundtrip;
+#[cfg(feature = "std")]
+mod version;
+
+#[cfg(feature = "std")]
+pub use codec::FormatCodec;
+#[cfg(any(
+    feature = "csv",
+    feature = "lx",
+    feature = "ogn",
+    all(feature = "tdb", feature = "std"),
+    feature = "xcsoar"
+))]
+pub use detect::{DetectError, DetectedFile, Format, decode_any, detect_format};
+#[cfg(feature = "std")]
+pub use diff::{FileDiff, Regression, join};
+pub use frequency::FrequencyBand;
+#[cfg(feature = "std")]
+pub use merge::{MergeError, MergePolicy, merge};
+pub use profile::FieldProfile;
+#[cfg(all(feature = "tdb", feature = "std"))]
+pub use record::RecordBuilder;
+#[cfg(all(feature = "tdb", feature = "xcsoar"))]
+pub use roundtrip::{CrossRoundtripError, CrossRoundtripReport, FieldChange, cross_roundtrip};
+#[cfg(feature = "std")]
+pub use version::version_from_filename;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A FlarmNet record.
+///
+/// Records compare and hash by their data, ignoring [Record::position].
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Record {
     pub flarm_id: String,
     pub pilot_name: String,
@@ -14,10 +86,74 @@ pub struct Record {
     pub registration: String,
     pub call_sign: String,
     pub frequency: String,
+    /// FLARM address type stored in the bits above the 24-bit FLARM id.
+    ///
+    /// This is only populated by the TDB decoder when
+    /// `tdb::DecodeOptions::mask_flarm_id` is set, and is otherwise `0`.
+    pub address_type: u8,
+    /// Contents of the reserved bytes of a TDB record, which are written
+    /// back unchanged by the TDB encoder. Records of other formats leave
+    /// this zeroed.
+    pub reserved: [u8; 8],
+    /// Position of the record in the file it was decoded from.
+    ///
+    /// This is only populated by the TDB decoder when
+    /// `tdb::DecodeOptions::record_positions` is set, and is otherwise
+    /// `None`. See [File::in_original_order].
+    pub position: Option<usize>,
+}
+
+impl Record {
+    /// Returns all fields apart from [Record::position], which is not part of
+    /// the record data.
+    #[allow(clippy::type_complexity)]
+    fn data(&self) -> (&str, &str, &str, &str, &str, &str, &str, u8, &[u8; 8]) {
+        let Record {
+            flarm_id,
+            pilot_name,
+            airfield,
+            plane_type,
+            registration,
+            call_sign,
+            frequency,
+            address_type,
+            reserved,
+            position: _,
+        } = self;
+
+        (
+            flarm_id,
+            pilot_name,
+            airfield,
+            plane_type,
+            registration,
+            call_sign,
+            frequency,
+            *address_type,
+            reserved,
+        )
+    }
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.data() == other.data()
+    }
+}
+
+impl Eq for Record {}
+
+impl core::hash::Hash for Record {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.data().hash(state);
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct File {
+    /// Revision of the FlarmNet data. The formats store it without
+    /// interpreting it.
     pub version: u32,
     pub records: Vec<Record>,
 }
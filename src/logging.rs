@@ -0,0 +1,18 @@
+use core::fmt::Display;
+
+/// Logs a warning for every record that failed to decode, and a summary of
+/// the decoded file.
+pub(crate) fn log_records<T, E: Display>(format: &str, version: u32, records: &[Result<T, E>]) {
+    let mut failed = 0;
+    for (index, result) in records.iter().enumerate() {
+        if let Err(error) = result {
+            log::warn!("failed to decode {format} record {index}: {error}");
+            failed += 1;
+        }
+    }
+
+    log::debug!(
+        "decoded {format} file version {version}: {} records, {failed} failed",
+        records.len()
+    );
+}
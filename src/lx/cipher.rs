@@ -1,6 +1,6 @@
 use std::io::{Read, Write};
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Reader<R: Read> {
     inner: R,
 }
@@ -21,7 +21,7 @@ impl<R: Read> Read for Reader<R> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Writer<W: Write> {
     inner: W,
 }
@@ -74,7 +74,10 @@ pub fn decode_file(file: &[u8]) -> Result<DecodedFile, DecodeError> {
         .children()
         .filter(|child| child.name() == "FLARMDATA")
         .map(convert)
-        .collect();
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "log")]
+    crate::logging::log_records("LX", version, &records);
 
     Ok(DecodedFile { version, records })
 }
@@ -141,6 +144,7 @@ fn convert(element: &Element) -> Result<Record, DecodeError> {
         registration,
         call_sign,
         frequency,
+        ..Default::default()
     })
 }
 
@@ -241,6 +245,18 @@ mod tests {
             registration: "D-KESH",
             call_sign: "AS",
             frequency: "123.500",
+            address_type: 0,
+            reserved: [
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            position: None,
         }
         "###);
     }
@@ -28,6 +28,7 @@ pub enum EncodeError {
 ///             registration: "D-0816".to_string(),
 ///             call_sign: "SG".to_string(),
 ///             frequency: "130.530".to_string(),
+///             ..Default::default()
 ///         }
 ///     ]
 /// };
@@ -28,3 +28,25 @@ mod encode;
 
 pub use decode::*;
 pub use encode::*;
+
+/// [FormatCodec](crate::FormatCodec) implementation for the LX format, using
+/// [decode_file] and [encode_file].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Codec;
+
+impl crate::FormatCodec for Codec {
+    type DecodeError = DecodeError;
+    type EncodeError = EncodeError;
+
+    fn decode(data: &[u8]) -> Result<crate::File, DecodeError> {
+        let decoded = decode_file(data)?;
+        Ok(crate::File {
+            version: decoded.version,
+            records: decoded.records.into_iter().collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn encode(file: &crate::File) -> Result<Vec<u8>, EncodeError> {
+        encode_file(file)
+    }
+}
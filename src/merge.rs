@@ -0,0 +1,188 @@
+use crate::diff::FlarmIdKey;
+use crate::{File, Record};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// What [merge] does when the same FLARM id appears more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the record that appears first.
+    #[default]
+    KeepFirst,
+    /// Keep the record that appears last.
+    KeepLast,
+    /// Fail with [MergeError::DuplicateFlarmId].
+    Error,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// `file` is the position of the input that repeated the FLARM id.
+    #[error("duplicate FLARM id {flarm_id} in file {file}")]
+    DuplicateFlarmId { flarm_id: String, file: usize },
+}
+
+/// Combines multiple files into one, deduplicating records by FLARM id
+/// according to `policy`.
+///
+/// FLARM ids are compared as described for [join](crate::join), and
+/// duplicates within a single file are handled like duplicates across files. The records keep
+/// the position where their FLARM id first appeared. The merged file has
+/// the highest version of the inputs, or `0` if there are none.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, MergePolicy, Record};
+/// let record = |id: &str, registration: &str| Record {
+///     flarm_id: id.to_string(),
+///     registration: registration.to_string(),
+///     ..Default::default()
+/// };
+/// let a = File { version: 3, records: vec![record("000001", "D-0816")] };
+/// let b = File {
+///     version: 5,
+///     records: vec![record("000002", "D-2188"), record("000001", "D-9527")],
+/// };
+///
+/// let merged = flarmnet::merge([a, b], MergePolicy::KeepLast).unwrap();
+/// assert_eq!(merged.version, 5);
+/// assert_eq!(merged.records, [record("000001", "D-9527"), record("000002", "D-2188")]);
+/// ```
+pub fn merge(
+    files: impl IntoIterator<Item = File>,
+    policy: MergePolicy,
+) -> Result<File, MergeError> {
+    let mut version = 0;
+    let mut records: Vec<Record> = Vec::new();
+    // positions in `records` by FLARM id
+    let mut positions: HashMap<FlarmIdKey, usize> = HashMap::new();
+
+    for (file_index, file) in files.into_iter().enumerate() {
+        version = version.max(file.version);
+
+        for record in file.records {
+            let flarm_id = FlarmIdKey::new(&record.flarm_id);
+            match positions.get(&flarm_id) {
+                None => {
+                    positions.insert(flarm_id, records.len());
+                    records.push(record);
+                }
+                Some(&position) => match policy {
+                    MergePolicy::KeepFirst => {}
+                    MergePolicy::KeepLast => records[position] = record,
+                    MergePolicy::Error => {
+                        return Err(MergeError::DuplicateFlarmId {
+                            flarm_id: record.flarm_id,
+                            file: file_index,
+                        });
+                    }
+                },
+            }
+        }
+    }
+
+    Ok(File { version, records })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    fn record(flarm_id: &str, registration: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn files() -> [File; 2] {
+        [
+            File {
+                version: 7,
+                records: vec![record("3EE3C7", "D-0816"), record("000001", "D-2188")],
+            },
+            File {
+                version: 2,
+                records: vec![record("000002", "D-9527"), record("3ee3c7", "D-1234")],
+            },
+        ]
+    }
+
+    #[test]
+    fn merging_keeps_first_record() {
+        let merged = merge(files(), MergePolicy::KeepFirst).unwrap();
+        assert_eq!(merged.version, 7);
+        assert_eq!(
+            merged.records,
+            [
+                record("3EE3C7", "D-0816"),
+                record("000001", "D-2188"),
+                record("000002", "D-9527"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merging_keeps_last_record() {
+        let merged = merge(files(), MergePolicy::KeepLast).unwrap();
+        assert_eq!(merged.version, 7);
+        assert_eq!(
+            merged.records,
+            [
+                record("3ee3c7", "D-1234"),
+                record("000001", "D-2188"),
+                record("000002", "D-9527"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merging_fails_for_duplicates() {
+        assert_debug_snapshot!(merge(files(), MergePolicy::Error).unwrap_err(), @r###"
+        DuplicateFlarmId {
+            flarm_id: "3ee3c7",
+            file: 1,
+        }
+        "###);
+
+        let [a, _] = files();
+        let b = File {
+            version: 1,
+            records: vec![record("000002", "D-9527")],
+        };
+        assert_eq!(merge([a, b], MergePolicy::Error).unwrap().records.len(), 3);
+    }
+
+    #[test]
+    fn merging_compares_flarm_ids_as_numbers() {
+        let a = File {
+            version: 1,
+            records: vec![record("00000A", "D-0816"), record("xyz", "D-2188")],
+        };
+        let b = File {
+            version: 2,
+            records: vec![record("a", "D-1234"), record("XYZ", "D-9527")],
+        };
+
+        let merged = merge([a, b], MergePolicy::KeepLast).unwrap();
+        assert_eq!(
+            merged.records,
+            [record("a", "D-1234"), record("XYZ", "D-9527")]
+        );
+    }
+
+    #[test]
+    fn merging_nothing_returns_empty_file() {
+        let merged = merge([], MergePolicy::Error).unwrap();
+        assert_eq!(
+            merged,
+            File {
+                version: 0,
+                records: vec![],
+            }
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::{Device, DeviceType};
+use crate::Record;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("expected 7 values, found {0}")]
+    UnexpectedValueCount(usize),
+    #[error("invalid device type: {0}")]
+    InvalidDeviceType(String),
+    #[error("invalid device id: {0}")]
+    InvalidDeviceId(String),
+    #[error("invalid flag: {0}")]
+    InvalidFlag(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodedFile {
+    pub devices: Vec<Result<Device, DecodeError>>,
+}
+
+/// Decodes a DDB CSV dump.
+///
+/// A leading UTF-8 byte order mark is ignored, and lines may end with
+/// either LF or CRLF. Empty lines and lines starting with `#`, like the
+/// header line, are skipped.
+///
+/// # Examples
+///
+/// ```
+/// let file = "#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED
+/// 'F','3EE3C7','LS-6','D-0816','SG','Y','Y'
+/// 'I','3D2A1B','ASK-21','D-2188','','Y','N'
+/// 'X','000001','','','','Y','Y'
+/// ";
+///
+/// let result = flarmnet::ogn::decode_file(file);
+/// assert_eq!(result.devices.len(), 3);
+/// assert_eq!(result.devices.iter().filter(|it| it.is_ok()).count(), 2);
+/// ```
+pub fn decode_file(file: &str) -> DecodedFile {
+    let file = file.strip_prefix('\u{FEFF}').unwrap_or(file);
+
+    let devices = file
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(decode_record)
+        .collect();
+
+    DecodedFile { devices }
+}
+
+/// Decodes a single line of a DDB CSV dump.
+///
+/// The values may also be unquoted, as in the dumps that the DDB exports
+/// without quotes.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::Record;
+/// # use flarmnet::ogn::{Device, DeviceType};
+/// let line = "'F','3EE3C7','LS-6','D-0816','SG','Y','N'";
+///
+/// let result = flarmnet::ogn::decode_record(line);
+/// assert_eq!(result.unwrap(), Device {
+///     device_type: DeviceType::Flarm,
+///     record: Record {
+///         flarm_id: "3EE3C7".to_string(),
+///         plane_type: "LS-6".to_string(),
+///         registration: "D-0816".to_string(),
+///         call_sign: "SG".to_string(),
+///         ..Default::default()
+///     },
+///     tracked: true,
+///     identified: false,
+/// });
+/// ```
+pub fn decode_record(line: &str) -> Result<Device, DecodeError> {
+    let values = split_values(line);
+    let [
+        device_type,
+        device_id,
+        aircraft_model,
+        registration,
+        cn,
+        tracked,
+        identified,
+    ] = <[&str; 7]>::try_from(values.as_slice())
+        .map_err(|_| DecodeError::UnexpectedValueCount(values.len()))?;
+
+    let device_type = DeviceType::from_code(device_type)
+        .ok_or_else(|| DecodeError::InvalidDeviceType(device_type.to_string()))?;
+
+    if device_id.len() != 6 || !device_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidDeviceId(device_id.to_string()));
+    }
+
+    Ok(Device {
+        device_type,
+        record: Record {
+            flarm_id: device_id.to_ascii_uppercase(),
+            plane_type: aircraft_model.trim().to_string(),
+            registration: registration.trim().to_string(),
+            call_sign: cn.trim().to_string(),
+            ..Default::default()
+        },
+        tracked: decode_flag(tracked)?,
+        identified: decode_flag(identified)?,
+    })
+}
+
+/// Splits a line into its values, removing the quotes around them.
+fn split_values(line: &str) -> Vec<&str> {
+    match line
+        .strip_prefix('\'')
+        .and_then(|line| line.strip_suffix('\''))
+    {
+        // values may contain commas, but never quotes
+        Some(line) => line.split("','").collect(),
+        None => line.split(',').collect(),
+    }
+}
+
+fn decode_flag(value: &str) -> Result<bool, DecodeError> {
+    match value {
+        "Y" => Ok(true),
+        "N" => Ok(false),
+        _ => Err(DecodeError::InvalidFlag(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_file, decode_record};
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn decoding_skips_header_and_empty_lines() {
+        let file = "\u{FEFF}#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED\r\n\
+                    'O','a1b2c3','Ka 6, CR','','','N','N'\r\n\
+                    \r\n";
+        assert_debug_snapshot!(decode_file(file), @r###"
+        DecodedFile {
+            devices: [
+                Ok(
+                    Device {
+                        device_type: Ogn,
+                        record: Record {
+                            flarm_id: "A1B2C3",
+                            pilot_name: "",
+                            airfield: "",
+                            plane_type: "Ka 6, CR",
+                            registration: "",
+                            call_sign: "",
+                            frequency: "",
+                            address_type: 0,
+                            reserved: [
+                                0,
+                                0,
+                                0,
+                                0,
+                                0,
+                                0,
+                                0,
+                                0,
+                            ],
+                            position: None,
+                        },
+                        tracked: false,
+                        identified: false,
+                    },
+                ),
+            ],
+        }
+        "###);
+    }
+
+    #[test]
+    fn decoding_accepts_unquoted_values() {
+        let line = "F,3EE3C7,LS-6,D-0816,SG,Y,Y";
+        assert_eq!(
+            decode_record(line).unwrap(),
+            decode_record("'F','3EE3C7','LS-6','D-0816','SG','Y','Y'").unwrap()
+        );
+    }
+
+    #[test]
+    fn decoding_fails_for_invalid_values() {
+        assert_debug_snapshot!(decode_record("'F','3EE3C7','LS-6','D-0816','SG','Y'"), @r###"
+        Err(
+            UnexpectedValueCount(
+                6,
+            ),
+        )
+        "###);
+        assert_debug_snapshot!(decode_record("'X','3EE3C7','LS-6','D-0816','SG','Y','Y'"), @r###"
+        Err(
+            InvalidDeviceType(
+                "X",
+            ),
+        )
+        "###);
+        assert_debug_snapshot!(decode_record("'F','3EE3C','LS-6','D-0816','SG','Y','Y'"), @r###"
+        Err(
+            InvalidDeviceId(
+                "3EE3C",
+            ),
+        )
+        "###);
+        assert_debug_snapshot!(decode_record("'F','3EE3CG','LS-6','D-0816','SG','Y','Y'"), @r###"
+        Err(
+            InvalidDeviceId(
+                "3EE3CG",
+            ),
+        )
+        "###);
+        assert_debug_snapshot!(decode_record("'F','3EE3C7','LS-6','D-0816','SG','y','Y'"), @r###"
+        Err(
+            InvalidFlag(
+                "y",
+            ),
+        )
+        "###);
+    }
+}
@@ -0,0 +1,191 @@
+use super::{Device, HEADER};
+use std::io::{Cursor, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EncodeError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The value contains quotes or line breaks, which the format can not
+    /// store.
+    #[error("invalid character(s): {0}")]
+    InvalidCharacter(String),
+    /// The device id is not six hex digits, which is the only form the DDB
+    /// stores.
+    #[error("invalid device id: {0}")]
+    InvalidDeviceId(String),
+}
+
+/// Encodes devices as a DDB CSV dump, with a header line.
+///
+/// Records of other formats can be converted with [Device::from].
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::Record;
+/// # use flarmnet::ogn::Device;
+/// let record = Record {
+///     flarm_id: "3EE3C7".to_string(),
+///     pilot_name: "Tobias Bieniek".to_string(),
+///     plane_type: "LS6a".to_string(),
+///     registration: "D-0816".to_string(),
+///     call_sign: "SG".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let result = flarmnet::ogn::encode_file(&[Device::from(record)]).unwrap();
+/// assert_eq!(result, b"#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED
+/// 'F','3EE3C7','LS6a','D-0816','SG','Y','Y'
+/// ");
+/// ```
+pub fn encode_file(devices: &[Device]) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    writer.write(devices)?;
+
+    let buffer = writer.into_inner().into_inner();
+
+    Ok(buffer)
+}
+
+/// Encodes a single device as a line of a DDB CSV dump, without the
+/// trailing newline.
+///
+/// # Examples
+///
+/// ```
+/// let line = "'I','3D2A1B','ASK-21','D-2188','','Y','N'";
+///
+/// let device = flarmnet::ogn::decode_record(line).unwrap();
+/// assert_eq!(flarmnet::ogn::encode_record(&device).unwrap(), line);
+/// ```
+pub fn encode_record(device: &Device) -> Result<String, EncodeError> {
+    let mut writer = Writer::new(Vec::new());
+    writer.write_values(device)?;
+
+    let buffer = writer.into_inner();
+
+    // the output only consists of the UTF-8 values and ASCII separators
+    Ok(String::from_utf8(buffer).unwrap())
+}
+
+#[derive(Debug, Clone)]
+pub struct Writer<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self { writer: inner }
+    }
+
+    pub fn write(&mut self, devices: &[Device]) -> Result<(), EncodeError> {
+        writeln!(self.writer, "{HEADER}")?;
+        for device in devices {
+            self.write_values(device)?;
+            self.writer.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_values(&mut self, device: &Device) -> Result<(), EncodeError> {
+        let flag = |value| if value { "Y" } else { "N" };
+        let record = &device.record;
+        let device_id = &record.flarm_id;
+        if device_id.len() != 6 || !device_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(EncodeError::InvalidDeviceId(device_id.clone()));
+        }
+
+        let values = [
+            device.device_type.code(),
+            device_id,
+            &record.plane_type,
+            &record.registration,
+            &record.call_sign,
+            flag(device.tracked),
+            flag(device.identified),
+        ];
+
+        for (i, value) in values.into_iter().enumerate() {
+            if value.contains(['\'', '\n', '\r']) {
+                return Err(EncodeError::InvalidCharacter(value.to_string()));
+            }
+            if i > 0 {
+                self.writer.write_all(b",")?;
+            }
+            write!(self.writer, "'{value}'")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EncodeError, encode_file, encode_record};
+    use crate::Record;
+    use crate::ogn::{Device, DeviceType, decode_file};
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn encoding_round_trips() {
+        let devices = vec![
+            Device {
+                device_type: DeviceType::Ogn,
+                record: Record {
+                    flarm_id: "A1B2C3".to_string(),
+                    plane_type: "Ka 6, CR".to_string(),
+                    ..Default::default()
+                },
+                tracked: false,
+                identified: false,
+            },
+            Device::from(Record {
+                flarm_id: "3EE3C7".to_string(),
+                plane_type: "LS6a".to_string(),
+                registration: "D-0816".to_string(),
+                call_sign: "SG".to_string(),
+                ..Default::default()
+            }),
+        ];
+
+        let encoded = encode_file(&devices).unwrap();
+        let decoded = decode_file(std::str::from_utf8(&encoded).unwrap());
+        let decoded: Vec<_> = decoded.devices.into_iter().map(Result::unwrap).collect();
+        assert_eq!(decoded, devices);
+    }
+
+    #[test]
+    fn encoding_fails_for_quotes() {
+        let device = Device::from(Record {
+            flarm_id: "3EE3C7".to_string(),
+            plane_type: "Duo Discus 'T'".to_string(),
+            ..Default::default()
+        });
+        assert_debug_snapshot!(encode_record(&device).unwrap_err(), @r###"
+        InvalidCharacter(
+            "Duo Discus 'T'",
+        )
+        "###);
+    }
+
+    #[test]
+    fn encoding_fails_for_invalid_device_ids() {
+        for flarm_id in ["", "3EE3C", "3EE3CG", "3EE3C7','X"] {
+            let device = Device::from(Record {
+                flarm_id: flarm_id.to_string(),
+                ..Default::default()
+            });
+            assert!(matches!(
+                encode_record(&device),
+                Err(EncodeError::InvalidDeviceId(id)) if id == flarm_id
+            ));
+            assert!(encode_file(&[device]).is_err());
+        }
+    }
+}
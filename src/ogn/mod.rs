@@ -0,0 +1,87 @@
+//! Decoder/Encoder for the [OGN Devices Database](https://ddb.glidernet.org/)
+//! (DDB) CSV dump.
+//!
+//! The file starts with a header line, and every following line holds one
+//! device with its values enclosed in single quotes:
+//!
+//! ```text
+//! #DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED
+//! 'F','3EE3C7','LS-6','D-0816','SG','Y','Y'
+//! ```
+//!
+//! The aircraft model, registration and competition number map onto the
+//! `plane_type`, `registration` and `call_sign` fields of a
+//! [Record]. The DDB has no pilot name, airfield or
+//! frequency. The device type and the `TRACKED`/`IDENTIFIED` privacy flags
+//! have no equivalent in [Record] and are kept in [Device]
+//! instead, so that devices whose owners opted out stay opted out when the
+//! file is written back.
+
+mod decode;
+mod encode;
+
+pub use decode::*;
+pub use encode::*;
+
+use crate::Record;
+
+/// Header line of the DDB CSV dump.
+const HEADER: &str = "#DEVICE_TYPE,DEVICE_ID,AIRCRAFT_MODEL,REGISTRATION,CN,TRACKED,IDENTIFIED";
+
+/// Kind of address that a [Device] is registered with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DeviceType {
+    /// A FLARM id, stored as `F`.
+    #[default]
+    Flarm,
+    /// An ICAO 24-bit aircraft address, stored as `I`.
+    Icao,
+    /// An OGN tracker id, stored as `O`.
+    Ogn,
+}
+
+impl DeviceType {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "F" => Some(DeviceType::Flarm),
+            "I" => Some(DeviceType::Icao),
+            "O" => Some(DeviceType::Ogn),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            DeviceType::Flarm => "F",
+            DeviceType::Icao => "I",
+            DeviceType::Ogn => "O",
+        }
+    }
+}
+
+/// A device of the DDB, consisting of its [Record] and the values that the
+/// other formats do not store.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Device {
+    pub device_type: DeviceType,
+    /// The device id is stored in [Record::flarm_id].
+    pub record: Record,
+    /// Whether the owner allows tracking the device.
+    pub tracked: bool,
+    /// Whether the owner allows showing the registration and competition
+    /// number.
+    pub identified: bool,
+}
+
+/// Wraps a record as a FLARM device that may be tracked and identified, e.g.
+/// for writing FlarmNet records to the DDB format.
+impl From<Record> for Device {
+    fn from(record: Record) -> Self {
+        Device {
+            device_type: DeviceType::Flarm,
+            record,
+            tracked: true,
+            identified: true,
+        }
+    }
+}
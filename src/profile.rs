@@ -0,0 +1,106 @@
+use crate::{File, Record};
+use alloc::vec::Vec;
+
+/// The fields a device requires to be non-empty, for use with
+/// [File::conforms_to].
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::FieldProfile;
+/// let profile = FieldProfile {
+///     flarm_id: true,
+///     frequency: true,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FieldProfile {
+    pub flarm_id: bool,
+    pub pilot_name: bool,
+    pub airfield: bool,
+    pub plane_type: bool,
+    pub registration: bool,
+    pub call_sign: bool,
+    pub frequency: bool,
+}
+
+impl FieldProfile {
+    /// Returns `true` if all fields required by this profile are non-empty.
+    ///
+    /// Fields that only contain whitespace count as empty.
+    pub fn matches(&self, record: &Record) -> bool {
+        [
+            (self.flarm_id, &record.flarm_id),
+            (self.pilot_name, &record.pilot_name),
+            (self.airfield, &record.airfield),
+            (self.plane_type, &record.plane_type),
+            (self.registration, &record.registration),
+            (self.call_sign, &record.call_sign),
+            (self.frequency, &record.frequency),
+        ]
+        .into_iter()
+        .all(|(required, value)| !required || !value.trim().is_empty())
+    }
+}
+
+impl File {
+    /// Returns the index of every record that does not contain all fields
+    /// required by the given [FieldProfile].
+    pub fn conforms_to(&self, profile: &FieldProfile) -> Vec<usize> {
+        self.records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| !profile.matches(record))
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FieldProfile, File, Record};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn conforms_to_works() {
+        let file = File {
+            version: 1,
+            records: vec![
+                Record {
+                    flarm_id: "3EE3C7".to_string(),
+                    call_sign: "SG".to_string(),
+                    frequency: "130.530".to_string(),
+                    ..Default::default()
+                },
+                Record {
+                    flarm_id: "000001".to_string(),
+                    frequency: "123.500".to_string(),
+                    ..Default::default()
+                },
+                Record {
+                    flarm_id: "000002".to_string(),
+                    call_sign: "XY".to_string(),
+                    frequency: " ".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let radio = FieldProfile {
+            flarm_id: true,
+            frequency: true,
+            ..Default::default()
+        };
+        assert_eq!(file.conforms_to(&radio), [2]);
+
+        let display = FieldProfile {
+            call_sign: true,
+            ..Default::default()
+        };
+        assert_eq!(file.conforms_to(&display), [1]);
+
+        assert!(file.conforms_to(&FieldProfile::default()).is_empty());
+    }
+}
@@ -0,0 +1,420 @@
+use crate::Record;
+use alloc::format;
+#[cfg(feature = "unicode-width")]
+use alloc::vec::Vec;
+
+impl Record {
+    /// Removes personal data from the record by clearing the pilot name.
+    ///
+    /// The FLARM id, plane type, airfield, registration, call sign and
+    /// frequency are kept intact. Use [Record::anonymize_with] to also blank
+    /// the registration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let mut record = Record {
+    ///     flarm_id: "3EE3C7".to_string(),
+    ///     pilot_name: "Tobias Bieniek".to_string(),
+    ///     airfield: "EDKA".to_string(),
+    ///     plane_type: "LS6a".to_string(),
+    ///     registration: "D-0816".to_string(),
+    ///     call_sign: "SG".to_string(),
+    ///     frequency: "130.530".to_string(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// record.anonymize();
+    /// assert_eq!(record.pilot_name, "");
+    /// assert_eq!(record.registration, "D-0816");
+    /// ```
+    pub fn anonymize(&mut self) {
+        self.anonymize_with(false);
+    }
+
+    /// Removes personal data from the record by clearing the pilot name and,
+    /// if `blank_registration` is set, the registration.
+    pub fn anonymize_with(&mut self, blank_registration: bool) {
+        self.pilot_name.clear();
+        if blank_registration {
+            self.registration.clear();
+        }
+    }
+
+    /// Returns the value of the string field with the given name, or `None`
+    /// if there is no such field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let record = Record { registration: "D-0816".to_string(), ..Default::default() };
+    ///
+    /// assert_eq!(record.field("registration"), Some("D-0816"));
+    /// assert_eq!(record.field("foo"), None);
+    /// ```
+    pub fn field(&self, name: &str) -> Option<&str> {
+        let value = match name {
+            "flarm_id" => &self.flarm_id,
+            "pilot_name" => &self.pilot_name,
+            "airfield" => &self.airfield,
+            "plane_type" => &self.plane_type,
+            "registration" => &self.registration,
+            "call_sign" => &self.call_sign,
+            "frequency" => &self.frequency,
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Returns the competition number of the call sign, without a leading
+    /// class letter, or `None` if the call sign is empty.
+    ///
+    /// A single letter followed by a digit is treated as the class prefix.
+    /// Other call signs are returned as they are, apart from surrounding
+    /// whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let record = |call_sign: &str| Record { call_sign: call_sign.to_string(), ..Default::default() };
+    ///
+    /// assert_eq!(record("X27").competition_number(), Some("27"));
+    /// assert_eq!(record("27").competition_number(), Some("27"));
+    /// assert_eq!(record("SG").competition_number(), Some("SG"));
+    /// assert_eq!(record("").competition_number(), None);
+    /// ```
+    pub fn competition_number(&self) -> Option<&str> {
+        let call_sign = self.call_sign.trim();
+        let mut chars = call_sign.chars();
+        let has_prefix = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.next().is_some_and(|c| c.is_ascii_digit());
+
+        let number = if has_prefix {
+            &call_sign[1..]
+        } else {
+            call_sign
+        };
+        (!number.is_empty()).then_some(number)
+    }
+
+    /// Returns `true` if the value of the given field fits into the TDB
+    /// format without being truncated.
+    ///
+    /// Fields without a byte limit (see [field_max_bytes](crate::tdb::field_max_bytes))
+    /// always fit, and unknown fields never do.
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    pub fn field_fits(&self, field: &str) -> bool {
+        let Some(value) = self.field(field) else {
+            return false;
+        };
+
+        crate::tdb::field_max_bytes(field).is_none_or(|max| value.len() <= max)
+    }
+
+    /// Returns the frequency in kHz, or `None` if the frequency is empty.
+    ///
+    /// The frequency is parsed like the TDB encoder does, and invalid values
+    /// fail with [EncodeError::InvalidFrequency](crate::tdb::EncodeError::InvalidFrequency).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let mut record = Record { frequency: "123.5".to_string(), ..Default::default() };
+    /// assert_eq!(record.frequency_khz().unwrap(), Some(123500));
+    ///
+    /// record.frequency = "".to_string();
+    /// assert_eq!(record.frequency_khz().unwrap(), None);
+    ///
+    /// record.frequency = "junk".to_string();
+    /// assert!(record.frequency_khz().is_err());
+    /// ```
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    pub fn frequency_khz(&self) -> Result<Option<u32>, crate::tdb::EncodeError> {
+        crate::tdb::parse_frequency(&self.frequency, false)
+    }
+
+    /// Sets the frequency from a value in kHz, formatted in MHz with three
+    /// decimals like the decoders return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let mut record = Record::default();
+    /// record.set_frequency_khz(123500);
+    /// assert_eq!(record.frequency, "123.500");
+    /// ```
+    pub fn set_frequency_khz(&mut self, khz: u32) {
+        self.frequency = format!("{}.{:03}", khz / 1000, khz % 1000);
+    }
+
+    /// Returns the names of the fields that would be truncated by the TDB
+    /// encoder, see [Record::field_fits].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let record = Record { pilot_name: "Tobias Maximilian Bieniek".to_string(), ..Default::default() };
+    ///
+    /// assert_eq!(record.truncation_report(), ["pilot_name"]);
+    /// ```
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    pub fn truncation_report(&self) -> Vec<&'static str> {
+        FIELD_NAMES
+            .into_iter()
+            .filter(|field| !self.field_fits(field))
+            .collect()
+    }
+
+    /// Returns a builder for a record that is validated for the TDB format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let record = Record::builder()
+    ///     .flarm_id("3EE3C7")
+    ///     .registration("D-0816")
+    ///     .frequency("123.5")
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(record.registration, "D-0816");
+    ///
+    /// assert!(Record::builder().flarm_id("XYZ").build().is_err());
+    /// ```
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    pub fn builder() -> RecordBuilder {
+        RecordBuilder::default()
+    }
+
+    /// Returns the number of columns the value of the given field occupies
+    /// on a fixed-character display, or `None` if there is no such field.
+    ///
+    /// East Asian wide characters count as two columns, and combining marks
+    /// as none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// let record = Record { pilot_name: "山田 太郎".to_string(), ..Default::default() };
+    ///
+    /// assert_eq!(record.display_width("pilot_name"), Some(9));
+    /// assert_eq!(record.pilot_name.len(), 13);
+    /// ```
+    #[cfg(feature = "unicode-width")]
+    pub fn display_width(&self, field: &str) -> Option<usize> {
+        use unicode_width::UnicodeWidthStr;
+
+        self.field(field).map(UnicodeWidthStr::width)
+    }
+
+    /// Returns the names of all fields whose [display width](Record::display_width)
+    /// exceeds `max_columns`.
+    #[cfg(feature = "unicode-width")]
+    pub fn fields_exceeding_width(&self, max_columns: usize) -> Vec<&'static str> {
+        FIELD_NAMES
+            .into_iter()
+            .filter(|field| self.display_width(field).is_some_and(|w| w > max_columns))
+            .collect()
+    }
+}
+
+/// Builder for [Record], see [Record::builder].
+#[cfg(all(feature = "tdb", feature = "std"))]
+#[derive(Debug, Clone, Default)]
+pub struct RecordBuilder {
+    record: Record,
+}
+
+#[cfg(all(feature = "tdb", feature = "std"))]
+impl RecordBuilder {
+    /// Sets [Record::flarm_id].
+    pub fn flarm_id(mut self, flarm_id: impl Into<String>) -> Self {
+        self.record.flarm_id = flarm_id.into();
+        self
+    }
+
+    /// Sets [Record::pilot_name].
+    pub fn pilot_name(mut self, pilot_name: impl Into<String>) -> Self {
+        self.record.pilot_name = pilot_name.into();
+        self
+    }
+
+    /// Sets [Record::airfield].
+    pub fn airfield(mut self, airfield: impl Into<String>) -> Self {
+        self.record.airfield = airfield.into();
+        self
+    }
+
+    /// Sets [Record::plane_type].
+    pub fn plane_type(mut self, plane_type: impl Into<String>) -> Self {
+        self.record.plane_type = plane_type.into();
+        self
+    }
+
+    /// Sets [Record::registration].
+    pub fn registration(mut self, registration: impl Into<String>) -> Self {
+        self.record.registration = registration.into();
+        self
+    }
+
+    /// Sets [Record::call_sign].
+    pub fn call_sign(mut self, call_sign: impl Into<String>) -> Self {
+        self.record.call_sign = call_sign.into();
+        self
+    }
+
+    /// Sets [Record::frequency].
+    pub fn frequency(mut self, frequency: impl Into<String>) -> Self {
+        self.record.frequency = frequency.into();
+        self
+    }
+
+    /// Returns the record, or the error that the TDB encoder would report
+    /// for its FLARM id or frequency.
+    ///
+    /// Values that are too long are not rejected, see
+    /// [Record::truncation_report].
+    pub fn build(self) -> Result<Record, crate::tdb::EncodeError> {
+        crate::tdb::parse_flarm_id(&self.record.flarm_id)?;
+        crate::tdb::parse_frequency(&self.record.frequency, false)?;
+        Ok(self.record)
+    }
+}
+
+#[cfg(any(all(feature = "tdb", feature = "std"), feature = "unicode-width"))]
+const FIELD_NAMES: [&str; 7] = [
+    "flarm_id",
+    "pilot_name",
+    "airfield",
+    "plane_type",
+    "registration",
+    "call_sign",
+    "frequency",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::Record;
+    use alloc::string::ToString;
+
+    #[test]
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    fn field_fits_works() {
+        let record = Record {
+            flarm_id: "3EE3C7".to_string(),
+            pilot_name: "0123456789ABCDE".to_string(),
+            airfield: "0123456789ABCDEF".to_string(),
+            plane_type: "01234567890123Ä".to_string(),
+            ..Default::default()
+        };
+
+        assert!(record.field_fits("flarm_id"));
+        assert!(record.field_fits("pilot_name"));
+        assert!(!record.field_fits("airfield"));
+        assert!(!record.field_fits("plane_type"));
+        assert!(record.field_fits("registration"));
+        assert!(!record.field_fits("foo"));
+    }
+
+    #[test]
+    fn competition_number_strips_class_prefix() {
+        let record = |call_sign: &str| Record {
+            call_sign: call_sign.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(record(" D1 ").competition_number(), Some("1"));
+        assert_eq!(record("1A").competition_number(), Some("1A"));
+        assert_eq!(record("ABC").competition_number(), Some("ABC"));
+        assert_eq!(record("Ä1").competition_number(), Some("Ä1"));
+        assert_eq!(record("X").competition_number(), Some("X"));
+        assert_eq!(record("  ").competition_number(), None);
+    }
+
+    #[test]
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    fn builder_validates_values() {
+        let record = Record::builder()
+            .flarm_id("3ee3c7")
+            .pilot_name("Tobias Bieniek")
+            .airfield("EDKA")
+            .plane_type("LS6a")
+            .registration("D-0816")
+            .call_sign("SG")
+            .frequency("130.530")
+            .build()
+            .unwrap();
+        assert_eq!(record.flarm_id, "3ee3c7");
+        assert_eq!(record.call_sign, "SG");
+        assert!(record.truncation_report().is_empty());
+
+        let error = Record::builder()
+            .flarm_id("000001")
+            .frequency("123,5")
+            .build();
+        assert_eq!(error.unwrap_err().to_string(), "invalid frequency: 123,5");
+        assert!(Record::builder().build().is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    fn truncation_report_lists_long_fields() {
+        let record = Record {
+            flarm_id: "3EE3C7".to_string(),
+            airfield: "0123456789ABCDEF".to_string(),
+            plane_type: "01234567890123Ä".to_string(),
+            call_sign: "0123456789ABCDE".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(record.truncation_report(), ["airfield", "plane_type"]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "tdb", feature = "std"))]
+    fn frequency_khz_round_trips() {
+        let mut record = Record::default();
+        for khz in [0, 118000, 121500, 123505, 136990, 999999] {
+            record.set_frequency_khz(khz);
+            assert_eq!(
+                record.frequency_khz().unwrap(),
+                Some(khz),
+                "{}",
+                record.frequency
+            );
+        }
+
+        record.set_frequency_khz(8330);
+        assert_eq!(record.frequency, "8.330");
+        record.frequency = "123.4567".to_string();
+        assert!(record.frequency_khz().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn fields_exceeding_width_works() {
+        let record = Record {
+            flarm_id: "3EE3C7".to_string(),
+            pilot_name: "山田 太郎".to_string(),
+            airfield: "Müllerstädt".to_string(),
+            plane_type: "Schempp-Hirth Discus".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(record.display_width("airfield"), Some(11));
+        assert_eq!(record.display_width("foo"), None);
+        assert_eq!(
+            record.fields_exceeding_width(8),
+            ["pilot_name", "airfield", "plane_type"]
+        );
+        assert_eq!(record.fields_exceeding_width(11), ["plane_type"]);
+    }
+}
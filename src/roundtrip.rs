@@ -0,0 +1,241 @@
+use crate::frequency::parse_khz;
+use crate::xcsoar::fields::*;
+use crate::{File, tdb, xcsoar};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CrossRoundtripError {
+    #[error(transparent)]
+    TdbEncode(#[from] tdb::EncodeError),
+    #[error(transparent)]
+    TdbDecode(#[from] tdb::DecodeError),
+    #[error(transparent)]
+    XcsoarEncode(#[from] xcsoar::EncodeError),
+    #[error(transparent)]
+    XcsoarDecode(#[from] xcsoar::DecodeError),
+}
+
+/// The result of [cross_roundtrip].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrossRoundtripReport {
+    /// Number of records that were converted.
+    pub records: usize,
+    /// Fields that changed beyond the expected truncation and formatting.
+    pub changes: Vec<FieldChange>,
+}
+
+impl CrossRoundtripReport {
+    /// Returns `true` if all fields survived the round trip as expected.
+    pub fn is_clean(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// A field that did not survive [cross_roundtrip] as expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Position of the record in the original `file.records`.
+    pub index: usize,
+    /// Name of the field, as used by [Record::field](crate::Record::field).
+    pub field: &'static str,
+    /// The value expected after truncation by both formats.
+    pub expected: String,
+    /// The value after the round trip.
+    pub actual: String,
+}
+
+/// Converts a file to TDB, back, to the XCSoar format (see [xcsoar]), and
+/// back again, and reports the fields that changed along the way.
+///
+/// Text fields are expected to be truncated to the limits of both formats
+/// and to lose surrounding whitespace, FLARM ids to be uppercased and
+/// zero-padded, and frequencies are compared by value in kHz, so `"123.5"`
+/// and `"123.500"` are considered equal. Any other change points to the two
+/// codecs disagreeing on a field's semantics.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![Record {
+///         flarm_id: "3ee3c7".to_string(),
+///         pilot_name: "Tobias Bieniek".to_string(),
+///         frequency: "123.5".to_string(),
+///         ..Default::default()
+///     }],
+/// };
+///
+/// let report = flarmnet::cross_roundtrip(&file).unwrap();
+/// assert_eq!(report.records, 1);
+/// assert!(report.is_clean());
+/// ```
+pub fn cross_roundtrip(file: &File) -> Result<CrossRoundtripReport, CrossRoundtripError> {
+    let encoded = tdb::encode_file(file)?;
+    let decoded = tdb::decode_file(&encoded)?;
+    let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    let intermediate = File {
+        version: decoded.version,
+        records,
+    };
+    let encoded = xcsoar::encode_file(&intermediate)?;
+    // the encoder only writes hex digits
+    let encoded = String::from_utf8(encoded).unwrap();
+    let decoded = xcsoar::decode_file(&encoded)?;
+    let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    // the TDB encoder sorts the records by FLARM id, keeping the order of
+    // duplicates, and has already rejected invalid ids
+    let mut original = file
+        .records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            (
+                tdb::parse_flarm_id(&record.flarm_id).unwrap(),
+                index,
+                record,
+            )
+        })
+        .collect::<Vec<_>>();
+    original.sort_by_key(|(id, _, _)| *id);
+
+    let mut changes = Vec::new();
+    for ((id, index, record), actual) in original.into_iter().zip(&records) {
+        let mut check = |field: &'static str, expected: String, actual: &str| {
+            if expected != actual {
+                changes.push(FieldChange {
+                    index,
+                    field,
+                    expected,
+                    actual: actual.to_string(),
+                });
+            }
+        };
+
+        check("flarm_id", format!("{id:06X}"), &actual.flarm_id);
+        let text_fields = [
+            ("pilot_name", PILOT_NAME_LENGTH),
+            ("airfield", AIRFIELD_LENGTH),
+            ("plane_type", PLANE_TYPE_LENGTH),
+            ("registration", REGISTRATION_LENGTH),
+            ("call_sign", CALL_SIGN_LENGTH),
+        ];
+        for (field, length) in text_fields {
+            let value = record.field(field).unwrap();
+            let actual = actual.field(field).unwrap();
+            check(field, truncated(field, value, length), actual);
+        }
+
+        if frequency_khz(&record.frequency) != frequency_khz(&actual.frequency) {
+            changes.push(FieldChange {
+                index,
+                field: "frequency",
+                expected: record.frequency.clone(),
+                actual: actual.frequency.clone(),
+            });
+        }
+    }
+
+    Ok(CrossRoundtripReport {
+        records: records.len(),
+        changes,
+    })
+}
+
+/// Applies the truncation of the TDB format followed by the truncation of
+/// the XCSoar format to a text field.
+fn truncated(field: &str, value: &str, length: usize) -> String {
+    let max_bytes = tdb::field_max_bytes(field).unwrap();
+    let value = &value[..value.floor_char_boundary(max_bytes)];
+    let value = value.chars().take(length).collect::<String>();
+    value.trim().to_string()
+}
+
+/// Parses a frequency in MHz like the encoders do, treating `0` like an
+/// empty frequency as the TDB format does.
+fn frequency_khz(value: &str) -> Option<u32> {
+    parse_khz(value.trim(), false).filter(|khz| *khz != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Record;
+    use insta::assert_debug_snapshot;
+
+    fn make_record(flarm_id: &str, pilot_name: &str, frequency: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            pilot_name: pilot_name.to_string(),
+            frequency: frequency.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cross_roundtrip_accepts_expected_truncation() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("00000f", "A pilot name that is too long", "123.5"),
+                make_record("000001", " Padded ", "0"),
+                make_record("000002", "Jürgen", ""),
+            ],
+        };
+
+        let report = cross_roundtrip(&file).unwrap();
+        assert_eq!(report.records, 3);
+        assert!(report.is_clean());
+    }
+
+    #[test]
+    fn cross_roundtrip_reports_changed_frequency() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "", "123.5"),
+                make_record("000000", "", "1234.567"),
+            ],
+        };
+
+        assert_debug_snapshot!(cross_roundtrip(&file).unwrap().changes, @r###"
+        [
+            FieldChange {
+                index: 1,
+                field: "frequency",
+                expected: "1234.567",
+                actual: "1234.56",
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn frequency_khz_parses_like_the_encoders() {
+        assert_eq!(frequency_khz("130.530"), Some(130530));
+        assert_eq!(frequency_khz(" 123.5 "), Some(123500));
+        assert_eq!(frequency_khz("0.000"), None);
+        assert_eq!(frequency_khz("-5"), None);
+        assert_eq!(frequency_khz("1.3053e2"), None);
+        assert_eq!(frequency_khz("inf"), None);
+    }
+
+    #[test]
+    fn cross_roundtrip_fails_for_non_latin1_values() {
+        let file = File {
+            version: 1,
+            records: vec![make_record("000001", "Łukasz", "")],
+        };
+
+        assert_debug_snapshot!(cross_roundtrip(&file).unwrap_err(), @r###"
+        XcsoarEncode(
+            InvalidEncoding(
+                "Łukasz",
+            ),
+        )
+        "###);
+    }
+}
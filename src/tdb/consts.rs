@@ -4,8 +4,12 @@ pub const INDEX_ENTRY_SIZE: usize = 4;
 pub const PADDING_SIZE: usize = 8;
 pub const RECORD_SIZE: usize = 96;
 
+pub const FLARM_ID_MASK: u32 = 0xFFFFFF;
+
 pub const FLARM_ID_OFFSET: usize = 0;
 pub const FREQUENCY_OFFSET: usize = 4;
+pub const RESERVED_OFFSET: usize = 8;
+pub const RESERVED_SIZE: usize = 8;
 pub const CALL_SIGN_OFFSET: usize = 16;
 pub const PILOT_NAME_OFFSET: usize = 32;
 pub const AIRFIELD_OFFSET: usize = 48;
@@ -1,84 +1,1048 @@
 use super::consts::*;
+use super::options::{DecodeOptions, EmptyFrequency, FieldPadding, FlarmIdCase};
 use crate::Record;
-use thiserror::Error;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
-#[derive(Error, Debug)]
+// `Display` is implemented by hand instead of with `thiserror`, which
+// requires `std`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DecodeError {
-    #[error("unexpected end of file")]
     UnexpectedEof,
-    #[error("invalid magic number: {0:02x?}")]
     InvalidMagic([u8; 4]),
-    #[error("invalid FLARM id: {0}")]
     InvalidFlarmId(u32),
-    #[error("invalid UTF-8 in {field} field at record offset {offset}")]
-    InvalidUtf8 { field: &'static str, offset: usize },
+    InvalidUtf8 {
+        field: &'static str,
+        offset: usize,
+    },
+    InvalidDeltaOperation(u8),
+    InvalidRecordSize(usize),
+    TrailingData {
+        extra: usize,
+    },
+    /// An I/O error, described by its `std::io::ErrorKind`. The variant also
+    /// exists without `std`, so that the variants do not depend on the
+    /// enabled features.
+    Io(String),
+    MalformedFlarmId(String),
+    IndexMismatch {
+        index: u32,
+        record: u32,
+        position: usize,
+    },
+    UnsortedIndex {
+        position: usize,
+    },
 }
 
-#[derive(Debug)]
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of file"),
+            DecodeError::InvalidMagic(magic) => write!(f, "invalid magic number: {magic:02x?}"),
+            DecodeError::InvalidFlarmId(id) => write!(f, "invalid FLARM id: {id}"),
+            DecodeError::InvalidUtf8 { field, offset } => {
+                write!(
+                    f,
+                    "invalid UTF-8 in {field} field at record offset {offset}"
+                )
+            }
+            DecodeError::InvalidDeltaOperation(op) => {
+                write!(f, "invalid delta operation: {op:#04x}")
+            }
+            DecodeError::InvalidRecordSize(size) => write!(f, "invalid record size: {size} bytes"),
+            DecodeError::TrailingData { extra } => {
+                write!(f, "unexpected {extra} bytes after the end of the file")
+            }
+            DecodeError::Io(kind) => write!(f, "I/O error: {kind}"),
+            DecodeError::MalformedFlarmId(id) => write!(f, "malformed FLARM id: {id}"),
+            DecodeError::IndexMismatch {
+                index,
+                record,
+                position,
+            } => write!(
+                f,
+                "index entry {index:#08x} does not match record {record:#08x} at position {position}"
+            ),
+            DecodeError::UnsortedIndex { position } => {
+                write!(f, "index is not sorted at position {position}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for DecodeError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::UnexpectedEof => DecodeError::UnexpectedEof,
+            kind => DecodeError::Io(kind.to_string()),
+        }
+    }
+}
+
+/// Serializes the error as its message, e.g. for records in a serialized
+/// [DecodedFile].
+#[cfg(feature = "serde")]
+impl serde::Serialize for DecodeError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// With the `serde` feature, records that failed to decode are serialized
+/// as `{"Err": "<message>"}`, and other records as `{"Ok": {...}}`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DecodedFile {
+    /// Version of the database, as stored in the header.
+    ///
+    /// This is the revision of the FlarmNet data, e.g. `28592`, and not a
+    /// version of the file format, which has not changed. It is therefore
+    /// passed through without validation.
     pub version: u32,
+    /// FLARM ids of the index block, as stored on disk.
+    pub index: Vec<u32>,
     pub records: Vec<Result<Record, DecodeError>>,
+    /// Number of bytes after the last record, e.g. records that were
+    /// appended without updating the record count in the header. These
+    /// bytes are not decoded.
+    pub trailing_bytes: usize,
 }
 
 pub fn decode_file(data: &[u8]) -> Result<DecodedFile, DecodeError> {
-    if data.len() < HEADER_SIZE {
-        return Err(DecodeError::UnexpectedEof);
+    decode_file_with(data, &DecodeOptions::default())
+}
+
+/// Decodes a FlarmNet file in TDB format using the given [DecodeOptions].
+pub fn decode_file_with(data: &[u8], options: &DecodeOptions) -> Result<DecodedFile, DecodeError> {
+    let layout = Layout::read(data, options)?;
+    if options.verify_index {
+        layout.verify_index(data)?;
     }
+    Ok(decode_layout(data, layout, options))
+}
+
+/// Decodes a FlarmNet file in TDB format like [decode_file], and passes
+/// every successfully decoded record through `f`.
+///
+/// Records for which `f` returns `None` are dropped. Records that failed to
+/// decode are kept as errors, and [DecodedFile::index] is not changed.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![
+///         Record { flarm_id: "3EE3C7".to_string(), pilot_name: "Tobias Bieniek".to_string(), ..Default::default() },
+///         Record { flarm_id: "000001".to_string(), ..Default::default() },
+///     ],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// let decoded = flarmnet::tdb::decode_file_with_filter(&data, |record| {
+///     let pilot_name = String::new();
+///     (record.flarm_id != "000001").then_some(Record { pilot_name, ..record })
+/// })
+/// .unwrap();
+/// assert_eq!(decoded.records.len(), 1);
+/// assert_eq!(decoded.records[0].as_ref().unwrap().pilot_name, "");
+/// ```
+pub fn decode_file_with_filter<F>(data: &[u8], mut f: F) -> Result<DecodedFile, DecodeError>
+where
+    F: FnMut(Record) -> Option<Record>,
+{
+    let options = DecodeOptions::default();
+    let layout = Layout::read(data, &options)?;
+    let records = RecordIter::new(data, layout, options)
+        .filter_map(|result| match result {
+            Ok(record) => f(record).map(Ok),
+            Err(error) => Some(Err(error)),
+        })
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "log")]
+    crate::logging::log_records("TDB", layout.version, &records);
+
+    Ok(DecodedFile {
+        version: layout.version,
+        index: layout_index(data, layout),
+        records,
+        trailing_bytes: data.len().saturating_sub(layout.end()),
+    })
+}
+
+/// Decodes a FlarmNet file in TDB format like [decode_file], but decodes the
+/// records on multiple threads.
+///
+/// The header and file size are validated before any records are decoded,
+/// and the records are returned in file order. A panic while decoding is
+/// resumed on the calling thread.
+#[cfg(feature = "parallel")]
+pub fn decode_file_parallel(data: &[u8]) -> Result<DecodedFile, DecodeError> {
+    let options = DecodeOptions::default();
+    let layout = Layout::read(data, &options)?;
+
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = layout.record_count.div_ceil(threads).max(1);
+    let records = std::thread::scope(|scope| {
+        let handles = (0..layout.record_count)
+            .step_by(chunk_size)
+            .map(|start| {
+                let end = (start + chunk_size).min(layout.record_count);
+                let options = &options;
+                scope.spawn(move || {
+                    (start..end)
+                        .map(|i| decode_record_with(layout.record_data(data, i), i, options))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    #[cfg(feature = "log")]
+    crate::logging::log_records("TDB", layout.version, &records);
+
+    Ok(DecodedFile {
+        version: layout.version,
+        index: layout_index(data, layout),
+        records,
+        trailing_bytes: data.len().saturating_sub(layout.end()),
+    })
+}
 
-    let magic: [u8; 4] = data[0..4].try_into().unwrap();
-    if magic != MAGIC {
-        return Err(DecodeError::InvalidMagic(magic));
+/// Reads and decodes the FlarmNet file in TDB format at the given path.
+///
+/// Errors while reading the file are returned as [DecodeError::Io].
+#[cfg(feature = "std")]
+pub fn decode_path<P: AsRef<Path>>(path: P) -> Result<DecodedFile, DecodeError> {
+    let data = std::fs::read(path)?;
+    decode_file(&data)
+}
+
+/// Statistics collected by [decode_file_timed].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// Size of the input data in bytes.
+    pub bytes: usize,
+    /// Number of records in the file, including records that failed to
+    /// decode.
+    pub records: usize,
+    /// Time it took to decode the file.
+    pub elapsed: Duration,
+}
+
+/// Decodes a FlarmNet file in TDB format like [decode_file], and measures
+/// how long decoding took.
+#[cfg(feature = "std")]
+pub fn decode_file_timed(data: &[u8]) -> Result<(DecodedFile, DecodeStats), DecodeError> {
+    let start = Instant::now();
+    let file = decode_file(data)?;
+    let stats = DecodeStats {
+        bytes: data.len(),
+        records: file.records.len(),
+        elapsed: start.elapsed(),
+    };
+
+    Ok((file, stats))
+}
+
+/// Decodes the records with positions in `start..end`, e.g. to recover the
+/// intact part of a damaged file.
+///
+/// Only the header has to be valid, and the file is not required to contain
+/// all records. Records whose data is missing from the file are reported as
+/// [DecodeError::UnexpectedEof]. The range is limited to the record count
+/// from the header.
+pub fn decode_record_range(
+    data: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<Vec<Result<Record, DecodeError>>, DecodeError> {
+    let layout = Layout::read_header(data)?;
+    let options = DecodeOptions::default();
+
+    let end = end.min(layout.record_count);
+    let records = (start.min(end)..end)
+        .map(|i| {
+            let start = layout.checked_record_offset(i);
+            let end = layout.checked_record_offset(i + 1);
+            match start.zip(end).and_then(|(start, end)| data.get(start..end)) {
+                Some(record) => decode_record_with(record.try_into().unwrap(), i, &options),
+                None => Err(DecodeError::UnexpectedEof),
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
+
+/// The outcome of decoding a single record, together with the raw bytes it
+/// was decoded from.
+#[derive(Debug, Clone)]
+pub struct RecordOutcome {
+    /// Position of the record in the file.
+    pub index: usize,
+    /// The raw record data, as stored on disk.
+    pub raw: [u8; RECORD_SIZE],
+    pub result: Result<Record, DecodeError>,
+    /// Text fields that contained invalid UTF-8 and were decoded with
+    /// [DecodeOptions::lossy_utf8].
+    pub lossy_fields: Vec<&'static str>,
+}
+
+/// Decodes the records of a FlarmNet file in TDB format, keeping the raw
+/// bytes of every record.
+///
+/// This is useful for reporting corrupt records, since the exact bytes that
+/// caused a [DecodeError] are available without re-reading the file.
+pub fn decode_outcomes(
+    data: &[u8],
+    options: &DecodeOptions,
+) -> Result<Vec<RecordOutcome>, DecodeError> {
+    let layout = Layout::read(data, options)?;
+
+    let outcomes = (0..layout.record_count)
+        .map(|index| {
+            let raw = layout.record_data(data, index);
+            let mut lossy_fields = Vec::new();
+            RecordOutcome {
+                index,
+                raw: *raw,
+                result: decode_record_tracking(raw, index, options, &mut lossy_fields),
+                lossy_fields,
+            }
+        })
+        .collect();
+
+    Ok(outcomes)
+}
+
+/// Decodes a buffer containing several concatenated FlarmNet files in TDB
+/// format.
+///
+/// The buffer is scanned for the TDB magic number, and each file found is
+/// decoded and skipped in turn. Bytes between files are ignored. If a file
+/// fails to decode, the error is reported and scanning continues after its
+/// magic number.
+pub fn decode_all(data: &[u8]) -> Vec<Result<DecodedFile, DecodeError>> {
+    let options = DecodeOptions::default();
+    let mut results = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = find_magic(&data[offset..]) {
+        let data = &data[offset + start..];
+        match Layout::read(data, &options) {
+            Ok(layout) => {
+                results.push(Ok(decode_layout(data, layout, &options)));
+                offset += start + layout.end();
+            }
+            Err(error) => {
+                results.push(Err(error));
+                offset += start + MAGIC.len();
+            }
+        }
     }
 
-    let version = u32::from_le_bytes(data[4..8].try_into().unwrap());
-    let record_count = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+    results
+}
 
-    let expected_size =
-        HEADER_SIZE + record_count * INDEX_ENTRY_SIZE + PADDING_SIZE + record_count * RECORD_SIZE;
-    if data.len() < expected_size {
-        return Err(DecodeError::UnexpectedEof);
+fn find_magic(data: &[u8]) -> Option<usize> {
+    data.windows(MAGIC.len()).position(|window| window == MAGIC)
+}
+
+fn decode_layout(data: &[u8], layout: Layout, options: &DecodeOptions) -> DecodedFile {
+    let index = layout_index(data, layout);
+    let records = RecordIter::new(data, layout, options.clone()).collect::<Vec<_>>();
+
+    #[cfg(feature = "log")]
+    crate::logging::log_records("TDB", layout.version, &records);
+
+    DecodedFile {
+        version: layout.version,
+        index,
+        records,
+        trailing_bytes: data.len().saturating_sub(layout.end()),
+    }
+}
+
+fn layout_index(data: &[u8], layout: Layout) -> Vec<u32> {
+    (0..layout.index_count)
+        .map(|i| layout.index_entry(data, i))
+        .collect()
+}
+
+/// Returns an iterator that decodes the records of a FlarmNet file in TDB
+/// format one at a time, without collecting them first.
+///
+/// The header is validated upfront. The iterator yields the same records as
+/// [DecodedFile::records] of [decode_file].
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// let records = flarmnet::tdb::iter_records(&data).unwrap();
+/// assert_eq!(records.filter(|record| record.is_ok()).count(), 1);
+/// ```
+pub fn iter_records(data: &[u8]) -> Result<RecordIter<'_>, DecodeError> {
+    let options = DecodeOptions::default();
+    let layout = Layout::read(data, &options)?;
+    Ok(RecordIter::new(data, layout, options))
+}
+
+/// Iterator over the records of a TDB file, see [iter_records].
+#[derive(Debug, Clone)]
+pub struct RecordIter<'a> {
+    data: &'a [u8],
+    layout: Layout,
+    options: DecodeOptions,
+    next: usize,
+}
+
+impl<'a> RecordIter<'a> {
+    fn new(data: &'a [u8], layout: Layout, options: DecodeOptions) -> Self {
+        Self {
+            data,
+            layout,
+            options,
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for RecordIter<'_> {
+    type Item = Result<Record, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.layout.record_count {
+            return None;
+        }
+
+        let position = self.next;
+        self.next += 1;
+        let data = self.layout.record_data(self.data, position);
+        Some(decode_record_with(data, position, &self.options))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.layout.record_count - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for RecordIter<'_> {}
+
+/// Detects the size of the padding between the index and the record data.
+///
+/// If the file ends exactly at the end of the record data, the padding is
+/// derived from the file size. Otherwise, padding sizes of up to 64 bytes are
+/// tried, starting with the standard 8 bytes. A padding size is only accepted
+/// if the FLARM ids of the first and last record match the corresponding
+/// index entries.
+///
+/// Returns `None` if the header is invalid, the file contains no records, or
+/// no matching padding size was found.
+pub fn detect_padding(data: &[u8]) -> Option<usize> {
+    let layout = Layout::read_header(data).ok()?;
+    if layout.record_count == 0 {
+        return None;
+    }
+
+    let fixed_size = layout
+        .record_count
+        .checked_mul(INDEX_ENTRY_SIZE + RECORD_SIZE)
+        .and_then(|size| size.checked_add(HEADER_SIZE));
+    let exact = fixed_size.and_then(|size| data.len().checked_sub(size));
+
+    exact
+        .into_iter()
+        .chain([PADDING_SIZE])
+        .chain(0..=MAX_DETECTED_PADDING)
+        .filter(|padding| *padding <= MAX_DETECTED_PADDING)
+        .find(|padding| {
+            layout
+                .with_padding(*padding)
+                .is_some_and(|layout| layout.index_matches_records(data))
+        })
+}
+
+const MAX_DETECTED_PADDING: usize = 64;
+
+/// Infers the size of the records of a file from its size and the record
+/// count in the header, assuming the standard padding size.
+///
+/// This can be used with [DecodeOptions::record_size] to decode variant
+/// files with larger records. Files with records smaller than the standard
+/// 96 bytes can not be decoded.
+///
+/// Returns `None` if the header is invalid, the file contains no records, or
+/// the record data can not be divided evenly into records.
+pub fn infer_record_size(data: &[u8]) -> Option<usize> {
+    let layout = Layout::read_header(data).ok()?;
+    if layout.record_count == 0 {
+        return None;
     }
 
-    let records_offset = HEADER_SIZE + record_count * INDEX_ENTRY_SIZE + PADDING_SIZE;
+    let records_size = data.len().checked_sub(layout.records_offset)?;
+    let record_size = records_size / layout.record_count;
+    (record_size > 0 && records_size % layout.record_count == 0).then_some(record_size)
+}
+
+/// Absolute byte offsets of the fields of a record within a TDB file, see
+/// [field_offsets].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldOffsets {
+    /// Offset of the start of the record.
+    pub record: usize,
+    pub flarm_id: usize,
+    pub frequency: usize,
+    pub call_sign: usize,
+    pub pilot_name: usize,
+    pub airfield: usize,
+    pub plane_type: usize,
+    pub registration: usize,
+}
+
+/// Returns the absolute byte offsets of the fields of the record with the
+/// given FLARM id, or `None` if the file contains no such record.
+///
+/// The FLARM id is compared case-insensitively. If the file contains the
+/// FLARM id more than once, the first record is used. Fails with
+/// [DecodeError::MalformedFlarmId] if `flarm_id` is not a valid FLARM id.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// let offsets = flarmnet::tdb::field_offsets(&data, "3ee3c7").unwrap().unwrap();
+/// assert_eq!(offsets.record, 24);
+/// assert_eq!(offsets.registration, 24 + 80);
+/// assert_eq!(flarmnet::tdb::field_offsets(&data, "000001").unwrap(), None);
+/// assert!(flarmnet::tdb::field_offsets(&data, "xyz").is_err());
+/// ```
+pub fn field_offsets(data: &[u8], flarm_id: &str) -> Result<Option<FieldOffsets>, DecodeError> {
+    let flarm_id = parse_lookup_flarm_id(flarm_id)?;
+    let layout = Layout::read(data, &DecodeOptions::default())?;
 
-    let records = (0..record_count)
+    let offsets = (0..layout.record_count)
+        .find(|i| record_flarm_id(layout.record_data(data, *i)) == flarm_id)
         .map(|i| {
-            let offset = records_offset + i * RECORD_SIZE;
-            let record_data: &[u8; 96] = data[offset..offset + RECORD_SIZE].try_into().unwrap();
-            decode_record(record_data)
+            let record = layout.record_offset(i);
+            FieldOffsets {
+                record,
+                flarm_id: record + FLARM_ID_OFFSET,
+                frequency: record + FREQUENCY_OFFSET,
+                call_sign: record + CALL_SIGN_OFFSET,
+                pilot_name: record + PILOT_NAME_OFFSET,
+                airfield: record + AIRFIELD_OFFSET,
+                plane_type: record + PLANE_TYPE_OFFSET,
+                registration: record + REGISTRATION_OFFSET,
+            }
+        });
+
+    Ok(offsets)
+}
+
+/// Decodes the record with the given FLARM id, or returns `None` if the file
+/// contains no such record.
+///
+/// The FLARM id is found with a binary search of the sorted index block, so
+/// that only the matching record is decoded. If the index contains the
+/// FLARM id more than once, any of the records may be returned. Fails with
+/// [DecodeError::MalformedFlarmId] if `flarm_id` is not a valid FLARM id.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// assert_eq!(flarmnet::tdb::lookup(&data, "3ee3c7").unwrap(), Some(file.records[0].clone()));
+/// assert_eq!(flarmnet::tdb::lookup(&data, "000001").unwrap(), None);
+/// assert!(flarmnet::tdb::lookup(&data, "xyz").is_err());
+/// ```
+pub fn lookup(data: &[u8], flarm_id: &str) -> Result<Option<Record>, DecodeError> {
+    let flarm_id = parse_lookup_flarm_id(flarm_id)?;
+    let options = DecodeOptions::default();
+    let layout = Layout::read(data, &options)?;
+
+    let Ok(i) = layout
+        .index_entries(data)
+        .binary_search_by_key(&flarm_id, |entry| u32::from_le_bytes(*entry))
+    else {
+        return Ok(None);
+    };
+
+    decode_record_with(layout.record_data(data, i), i, &options).map(Some)
+}
+
+/// Parses the FLARM id that [lookup] and [field_offsets] search for.
+fn parse_lookup_flarm_id(flarm_id: &str) -> Result<u32, DecodeError> {
+    u32::from_str_radix(flarm_id, 16)
+        .ok()
+        .filter(|id| *id <= FLARM_ID_MASK)
+        .ok_or_else(|| DecodeError::MalformedFlarmId(flarm_id.to_string()))
+}
+
+/// Decodes a FlarmNet file in TDB format incrementally from a [Read]
+/// implementation, without loading the whole file into memory.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// # use flarmnet::tdb::Reader;
+/// let file = File {
+///     version: 1,
+///     records: vec![Record { flarm_id: "3EE3C7".to_string(), ..Default::default() }],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// let mut reader = Reader::new(data.as_slice());
+/// assert_eq!(reader.read_header().unwrap(), (1, 1));
+/// assert_eq!(reader.read_record().unwrap().unwrap().unwrap(), file.records[0]);
+/// assert!(reader.read_record().unwrap().is_none());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct Reader<R: Read> {
+    reader: R,
+    options: DecodeOptions,
+    header: Option<(u32, usize)>,
+    /// Number of records left to read, or `None` if the records are read
+    /// until the end of the input.
+    remaining: Option<usize>,
+    /// Position of the next record.
+    next: usize,
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, DecodeOptions::default())
+    }
+
+    /// Creates a reader that decodes records with the given [DecodeOptions].
+    pub fn with_options(inner: R, options: DecodeOptions) -> Self {
+        Self {
+            reader: inner,
+            options,
+            header: None,
+            remaining: None,
+            next: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Reads the header, index and padding, and returns the version and the
+    /// record count from the header.
+    ///
+    /// Calling this again returns the same values without reading anything.
+    /// If [DecodeOptions::trust_header_count] is disabled, the records are
+    /// read until the end of the input instead.
+    pub fn read_header(&mut self) -> Result<(u32, usize), DecodeError> {
+        if let Some(header) = self.header {
+            return Ok(header);
+        }
+
+        let mut header = [0; HEADER_SIZE];
+        self.reader.read_exact(&mut header)?;
+        let layout = Layout::read_header(&header)?;
+
+        let record_size = self.options.record_size.unwrap_or(RECORD_SIZE);
+        if record_size < RECORD_SIZE {
+            return Err(DecodeError::InvalidRecordSize(record_size));
+        }
+
+        let mut skip = self.options.padding.unwrap_or(PADDING_SIZE);
+        if self.options.trust_header_count {
+            skip = layout
+                .record_count
+                .checked_mul(INDEX_ENTRY_SIZE)
+                .and_then(|size| size.checked_add(skip))
+                .ok_or(DecodeError::UnexpectedEof)?;
+            self.remaining = Some(layout.record_count);
+        }
+        self.skip(skip)?;
+
+        self.buffer = vec![0; record_size];
+        self.header = Some((layout.version, layout.record_count));
+        Ok((layout.version, layout.record_count))
+    }
+
+    /// Reads the next record, or returns `None` after the last record.
+    ///
+    /// Reads the header first if [Reader::read_header] has not been called
+    /// yet. The outer error is returned if the input can not be read, e.g.
+    /// because it ends in the middle of a record, and the inner error if the
+    /// record data is invalid.
+    pub fn read_record(&mut self) -> Result<Option<Result<Record, DecodeError>>, DecodeError> {
+        self.read_header()?;
+
+        match self.remaining {
+            Some(0) => {
+                if self.options.exact_length {
+                    let extra = io::copy(&mut self.reader, &mut io::sink())? as usize;
+                    if extra > 0 {
+                        return Err(DecodeError::TrailingData { extra });
+                    }
+                }
+                return Ok(None);
+            }
+            Some(ref mut remaining) => {
+                self.reader.read_exact(&mut self.buffer)?;
+                *remaining -= 1;
+            }
+            None => {
+                if !read_exact_or_eof(&mut self.reader, &mut self.buffer)? {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let data = self.buffer[..RECORD_SIZE].try_into().unwrap();
+        let position = self.next;
+        self.next += 1;
+        Ok(Some(decode_record_with(data, position, &self.options)))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), DecodeError> {
+        let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())?;
+        if skipped < len as u64 {
+            return Err(DecodeError::UnexpectedEof);
+        }
+        Ok(())
+    }
+}
+
+/// Fills the buffer, or returns `false` if the input ends before the first
+/// byte.
+#[cfg(feature = "std")]
+fn read_exact_or_eof(reader: &mut impl Read, buffer: &mut [u8]) -> Result<bool, DecodeError> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(DecodeError::UnexpectedEof),
+            Ok(n) => filled += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    Ok(true)
+}
+
+/// Positions of the sections of a TDB file.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Layout {
+    pub(super) version: u32,
+    pub(super) record_count: usize,
+    /// Number of entries in the index block.
+    index_count: usize,
+    records_offset: usize,
+    record_size: usize,
+}
+
+impl Layout {
+    /// Reads the header and checks that the file is large enough to contain
+    /// all records.
+    ///
+    /// Record counts and sizes whose offsets overflow `usize` are reported as
+    /// [DecodeError::UnexpectedEof], since no such file fits into memory.
+    pub(super) fn read(data: &[u8], options: &DecodeOptions) -> Result<Self, DecodeError> {
+        let mut layout = Self::read_header(data)?;
+        let padding = options.padding.unwrap_or(PADDING_SIZE);
+
+        layout.record_size = options.record_size.unwrap_or(RECORD_SIZE);
+        if layout.record_size < RECORD_SIZE {
+            return Err(DecodeError::InvalidRecordSize(layout.record_size));
+        }
+
+        if !options.trust_header_count {
+            // assume that neither the record count nor the index were written
+            let records_size = data
+                .len()
+                .saturating_sub(HEADER_SIZE.saturating_add(padding));
+            layout.record_count = records_size / layout.record_size;
+            layout.index_count = 0;
+        }
+
+        let layout = layout
+            .with_padding(padding)
+            .ok_or(DecodeError::UnexpectedEof)?;
+
+        let end = layout
+            .checked_end()
+            .filter(|end| *end <= data.len())
+            .ok_or(DecodeError::UnexpectedEof)?;
+        if options.exact_length && data.len() > end {
+            let extra = data.len() - end;
+            return Err(DecodeError::TrailingData { extra });
+        }
+
+        Ok(layout)
+    }
+
+    /// Reads the header, assuming the standard padding size.
+    fn read_header(data: &[u8]) -> Result<Self, DecodeError> {
+        if data.len() < HEADER_SIZE {
+            return Err(DecodeError::UnexpectedEof);
+        }
+
+        let magic: [u8; 4] = data[0..4].try_into().unwrap();
+        if magic != MAGIC {
+            return Err(DecodeError::InvalidMagic(magic));
+        }
+
+        let version = u32::from_le_bytes(data[4..8].try_into().unwrap());
+        let record_count = u32::from_le_bytes(data[8..12].try_into().unwrap()) as usize;
+
+        Self {
+            version,
+            record_count,
+            index_count: record_count,
+            records_offset: 0,
+            record_size: RECORD_SIZE,
+        }
+        .with_padding(PADDING_SIZE)
+        .ok_or(DecodeError::UnexpectedEof)
+    }
+
+    /// Returns the layout with the given padding, or `None` if the offset of
+    /// the records overflows.
+    fn with_padding(self, padding: usize) -> Option<Self> {
+        let records_offset = self
+            .index_count
+            .checked_mul(INDEX_ENTRY_SIZE)?
+            .checked_add(HEADER_SIZE)?
+            .checked_add(padding)?;
+        Some(Self {
+            records_offset,
+            ..self
         })
-        .collect();
+    }
+
+    /// Returns the offset of the end of the record data. This must only be
+    /// used for layouts that were checked by [Layout::read].
+    fn end(&self) -> usize {
+        self.record_offset(self.record_count)
+    }
+
+    fn checked_end(&self) -> Option<usize> {
+        self.checked_record_offset(self.record_count)
+    }
 
-    Ok(DecodedFile { version, records })
+    fn index_entry(&self, data: &[u8], i: usize) -> u32 {
+        let offset = HEADER_SIZE + i * INDEX_ENTRY_SIZE;
+        u32::from_le_bytes(data[offset..offset + INDEX_ENTRY_SIZE].try_into().unwrap())
+    }
+
+    /// Returns the entries of the index block that have a matching record.
+    fn index_entries<'a>(&self, data: &'a [u8]) -> &'a [[u8; INDEX_ENTRY_SIZE]] {
+        let count = self.index_count.min(self.record_count);
+        let (entries, _) = data[HEADER_SIZE..HEADER_SIZE + count * INDEX_ENTRY_SIZE].as_chunks();
+        entries
+    }
+
+    /// Checks that the index entries match the records and are sorted.
+    fn verify_index(&self, data: &[u8]) -> Result<(), DecodeError> {
+        let mut previous = 0;
+        for (position, entry) in self.index_entries(data).iter().enumerate() {
+            let index = u32::from_le_bytes(*entry);
+            let record = record_flarm_id(self.record_data(data, position));
+            if index != record {
+                return Err(DecodeError::IndexMismatch {
+                    index,
+                    record,
+                    position,
+                });
+            }
+            if index < previous {
+                return Err(DecodeError::UnsortedIndex { position });
+            }
+            previous = index;
+        }
+        Ok(())
+    }
+
+    fn record_offset(&self, i: usize) -> usize {
+        self.records_offset + i * self.record_size
+    }
+
+    fn checked_record_offset(&self, i: usize) -> Option<usize> {
+        i.checked_mul(self.record_size)?
+            .checked_add(self.records_offset)
+    }
+
+    /// Returns the standard 96 bytes of the record at position `i`. For files
+    /// with larger records, the remaining bytes are ignored.
+    pub(super) fn record_data<'a>(&self, data: &'a [u8], i: usize) -> &'a [u8; RECORD_SIZE] {
+        let offset = self.record_offset(i);
+        data[offset..offset + RECORD_SIZE].try_into().unwrap()
+    }
+
+    fn index_matches_records(&self, data: &[u8]) -> bool {
+        if self.record_count == 0 || self.checked_end().is_none_or(|end| data.len() < end) {
+            return false;
+        }
+
+        [0, self.record_count - 1]
+            .into_iter()
+            .all(|i| record_flarm_id(self.record_data(data, i)) == self.index_entry(data, i))
+    }
 }
 
-fn decode_record(data: &[u8; 96]) -> Result<Record, DecodeError> {
-    let flarm_id = u32::from_le_bytes(
+/// Returns the raw FLARM id stored in the record data.
+pub(super) fn record_flarm_id(data: &[u8; RECORD_SIZE]) -> u32 {
+    u32::from_le_bytes(
         data[FLARM_ID_OFFSET..FLARM_ID_OFFSET + 4]
             .try_into()
             .unwrap(),
-    );
-    if flarm_id > 0xFFFFFF {
+    )
+}
+
+/// Decodes a single record, as it is stored on disk, without the header and
+/// index of a file.
+///
+/// # Examples
+///
+/// ```
+/// let mut data = [0; flarmnet::tdb::RECORD_SIZE];
+/// data[..4].copy_from_slice(&0x3EE3C7u32.to_le_bytes());
+/// data[80..86].copy_from_slice(b"D-0816");
+///
+/// let record = flarmnet::tdb::decode_record(&data).unwrap();
+/// assert_eq!(record.flarm_id, "3EE3C7");
+/// assert_eq!(record.registration, "D-0816");
+/// ```
+pub fn decode_record(data: &[u8; RECORD_SIZE]) -> Result<Record, DecodeError> {
+    decode_record_with(data, 0, &DecodeOptions::default())
+}
+
+/// Decodes the record at `position` in the file.
+fn decode_record_with(
+    data: &[u8; RECORD_SIZE],
+    position: usize,
+    options: &DecodeOptions,
+) -> Result<Record, DecodeError> {
+    decode_record_tracking(data, position, options, &mut Vec::new())
+}
+
+/// Decodes the record at `position` in the file, and adds the text fields
+/// that were decoded with [DecodeOptions::lossy_utf8] to `lossy_fields`.
+fn decode_record_tracking(
+    data: &[u8; 96],
+    position: usize,
+    options: &DecodeOptions,
+    lossy_fields: &mut Vec<&'static str>,
+) -> Result<Record, DecodeError> {
+    let flarm_id = record_flarm_id(data);
+    let address_type = if options.mask_flarm_id {
+        (flarm_id >> 24) as u8
+    } else if flarm_id > FLARM_ID_MASK {
+        return Err(DecodeError::InvalidFlarmId(flarm_id));
+    } else {
+        0
+    };
+    if options.strict_flarm_id && flarm_id & FLARM_ID_MASK == 0 {
         return Err(DecodeError::InvalidFlarmId(flarm_id));
     }
-    let flarm_id = format!("{:06X}", flarm_id);
+    let flarm_id = match options.flarm_id_case {
+        FlarmIdCase::Upper => format!("{:06X}", flarm_id & FLARM_ID_MASK),
+        FlarmIdCase::Lower => format!("{:06x}", flarm_id & FLARM_ID_MASK),
+    };
 
     let frequency = u32::from_le_bytes(
         data[FREQUENCY_OFFSET..FREQUENCY_OFFSET + 4]
             .try_into()
             .unwrap(),
     );
-    let frequency = if frequency == 0 {
+    let is_empty = match options.empty_frequency {
+        EmptyFrequency::Zero => frequency == 0,
+        EmptyFrequency::Sentinel(sentinel) => frequency == sentinel,
+    };
+    let frequency = if is_empty {
         String::new()
+    } else if options.raw_frequency {
+        frequency.to_string()
     } else {
         format!("{}.{:03}", frequency / 1000, frequency % 1000)
     };
 
-    let call_sign = decode_string(data, CALL_SIGN_OFFSET, "call_sign")?;
-    let pilot_name = decode_string(data, PILOT_NAME_OFFSET, "pilot_name")?;
-    let airfield = decode_string(data, AIRFIELD_OFFSET, "airfield")?;
-    let plane_type = decode_string(data, PLANE_TYPE_OFFSET, "plane_type")?;
-    let registration = decode_string(data, REGISTRATION_OFFSET, "registration")?;
+    let mut decode_text = |offset, field| -> Result<String, DecodeError> {
+        let mut value = match decode_string(data, offset, field) {
+            Err(DecodeError::InvalidUtf8 { .. }) if options.lossy_utf8 => {
+                lossy_fields.push(field);
+                String::from_utf8_lossy(field_content(data, offset)).into_owned()
+            }
+            result => result?,
+        };
+        if options.field_padding == FieldPadding::Space {
+            value.truncate(value.trim_end_matches(' ').len());
+        }
+        if options.trim_fields {
+            // ASCII whitespace and control characters all precede the space
+            value.truncate(value.trim_end_matches(|c: char| c <= ' ').len());
+        }
+        if options.normalize_placeholders && options.is_placeholder(&value) {
+            value.clear();
+        }
+        Ok(if options.case_fold {
+            value.to_lowercase()
+        } else {
+            value
+        })
+    };
+
+    let call_sign = decode_text(CALL_SIGN_OFFSET, "call_sign")?;
+    let pilot_name = decode_text(PILOT_NAME_OFFSET, "pilot_name")?;
+    let airfield = decode_text(AIRFIELD_OFFSET, "airfield")?;
+    let plane_type = decode_text(PLANE_TYPE_OFFSET, "plane_type")?;
+    let registration = decode_text(REGISTRATION_OFFSET, "registration")?;
 
     Ok(Record {
         flarm_id,
@@ -88,6 +1052,11 @@ fn decode_record(data: &[u8; 96]) -> Result<Record, DecodeError> {
         registration,
         call_sign,
         frequency,
+        address_type,
+        reserved: data[RESERVED_OFFSET..RESERVED_OFFSET + RESERVED_SIZE]
+            .try_into()
+            .unwrap(),
+        position: options.record_positions.then_some(position),
     })
 }
 
@@ -96,23 +1065,51 @@ fn decode_string(
     offset: usize,
     field: &'static str,
 ) -> Result<String, DecodeError> {
+    core::str::from_utf8(field_content(data, offset))
+        .map(|s| s.to_string())
+        .map_err(|_| DecodeError::InvalidUtf8 { field, offset })
+}
+
+/// Returns the bytes of the text field at `offset` up to the first NUL byte.
+fn field_content(data: &[u8; 96], offset: usize) -> &[u8] {
     let field_bytes = &data[offset..offset + STRING_FIELD_SIZE];
 
     let end = field_bytes
         .iter()
         .position(|&b| b == 0)
         .unwrap_or(STRING_FIELD_SIZE);
-    let content = &field_bytes[..end];
-
-    std::str::from_utf8(content)
-        .map(|s| s.to_string())
-        .map_err(|_| DecodeError::InvalidUtf8 { field, offset })
+    &field_bytes[..end]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
     use insta::assert_debug_snapshot;
+    #[cfg(feature = "serde")]
+    use insta::assert_snapshot;
+
+    #[test]
+    fn decode_error_variants_do_not_depend_on_std() {
+        // exhaustive, so that it stops compiling if a variant is feature-gated
+        let name = |error: &DecodeError| match error {
+            DecodeError::UnexpectedEof => "UnexpectedEof",
+            DecodeError::InvalidMagic(_) => "InvalidMagic",
+            DecodeError::InvalidFlarmId(_) => "InvalidFlarmId",
+            DecodeError::InvalidUtf8 { .. } => "InvalidUtf8",
+            DecodeError::InvalidDeltaOperation(_) => "InvalidDeltaOperation",
+            DecodeError::InvalidRecordSize(_) => "InvalidRecordSize",
+            DecodeError::TrailingData { .. } => "TrailingData",
+            DecodeError::Io(_) => "Io",
+            DecodeError::MalformedFlarmId(_) => "MalformedFlarmId",
+            DecodeError::IndexMismatch { .. } => "IndexMismatch",
+            DecodeError::UnsortedIndex { .. } => "UnsortedIndex",
+        };
+
+        let error = DecodeError::Io("entity not found".to_string());
+        assert_eq!(name(&error), "Io");
+        assert_eq!(error.to_string(), "I/O error: entity not found");
+    }
 
     #[test]
     fn decoding_fails_for_empty_file() {
@@ -127,6 +1124,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decoding_fails_for_huge_record_count() {
+        let mut data = make_valid_file(&[make_record(0x3EE3C7, 0, b"", b"", b"", b"")]);
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        assert_eq!(decode_file(&data).unwrap_err(), DecodeError::UnexpectedEof);
+        assert_eq!(detect_padding(&data), None);
+        let records = decode_record_range(&data, 0, 3).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records[1..].iter().all(Result::is_err));
+
+        #[cfg(feature = "std")]
+        {
+            let mut reader = Reader::new(data.as_slice());
+            assert_eq!(
+                reader.read_header().unwrap_err(),
+                DecodeError::UnexpectedEof
+            );
+        }
+    }
+
+    #[test]
+    fn decoding_fails_for_overflowing_layout() {
+        let data = make_valid_file(&[make_record(0x3EE3C7, 0, b"", b"", b"", b"")]);
+
+        let options = DecodeOptions::builder().record_size(usize::MAX).build();
+        assert_eq!(
+            decode_file_with(&data, &options).unwrap_err(),
+            DecodeError::UnexpectedEof
+        );
+
+        let options = DecodeOptions::builder().padding(usize::MAX).build();
+        assert_eq!(
+            decode_file_with(&data, &options).unwrap_err(),
+            DecodeError::UnexpectedEof
+        );
+
+        let options = DecodeOptions::builder()
+            .padding(usize::MAX)
+            .trust_header_count(false)
+            .build();
+        assert_eq!(
+            decode_file_with(&data, &options).unwrap_err(),
+            DecodeError::UnexpectedEof
+        );
+    }
+
     #[test]
     fn decoding_fails_for_invalid_magic() {
         let data = [
@@ -157,6 +1201,10 @@ mod tests {
     }
 
     fn make_valid_file(records: &[[u8; RECORD_SIZE]]) -> Vec<u8> {
+        make_file_with_padding(records, PADDING_SIZE)
+    }
+
+    fn make_file_with_padding(records: &[[u8; RECORD_SIZE]], padding: usize) -> Vec<u8> {
         let n = records.len() as u32;
         let mut data = Vec::new();
 
@@ -172,7 +1220,7 @@ mod tests {
         }
 
         // padding
-        data.extend_from_slice(&[0u8; PADDING_SIZE]);
+        data.extend_from_slice(&vec![0u8; padding]);
 
         // records
         for record in records {
@@ -222,6 +1270,7 @@ mod tests {
         let data = make_valid_file(&[record]);
         let result = decode_file(&data).unwrap();
         assert_eq!(result.version, 1);
+        assert_eq!(result.index, vec![0x3EE3C7]);
         assert_eq!(result.records.len(), 1);
         assert_debug_snapshot!(result.records[0].as_ref().unwrap(), @r###"
         Record {
@@ -232,6 +1281,18 @@ mod tests {
             registration: "D-0816",
             call_sign: "SG",
             frequency: "123.500",
+            address_type: 0,
+            reserved: [
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ],
+            position: None,
         }
         "###);
     }
@@ -245,6 +1306,717 @@ mod tests {
         assert_eq!(record.frequency, "");
     }
 
+    #[test]
+    fn decoding_maps_sentinel_frequency_to_empty() {
+        let records = [
+            make_record(0x000001, 0xFFFFFFFF, b"", b"", b"", b""),
+            make_record(0x000002, 0, b"", b"", b"", b""),
+        ];
+        let data = make_valid_file(&records);
+        let options = DecodeOptions {
+            empty_frequency: EmptyFrequency::Sentinel(0xFFFFFFFF),
+            ..Default::default()
+        };
+        let result = decode_file_with(&data, &options).unwrap();
+        assert_eq!(result.records[0].as_ref().unwrap().frequency, "");
+        assert_eq!(result.records[1].as_ref().unwrap().frequency, "0.000");
+    }
+
+    #[test]
+    fn decoding_works_with_custom_padding() {
+        let records = [
+            make_record(0x000001, 0, b"", b"", b"", b"D-0001"),
+            make_record(0x3EE3C7, 0, b"SG", b"", b"", b"D-0816"),
+        ];
+        let data = make_file_with_padding(&records, 16);
+        assert_eq!(detect_padding(&data), Some(16));
+
+        let options = DecodeOptions {
+            padding: Some(16),
+            ..Default::default()
+        };
+        let result = decode_file_with(&data, &options).unwrap();
+        let registrations: Vec<&str> = result
+            .records
+            .iter()
+            .map(|r| r.as_ref().unwrap().registration.as_str())
+            .collect();
+        assert_eq!(registrations, vec!["D-0001", "D-0816"]);
+    }
+
+    #[test]
+    fn detecting_padding_works_with_trailing_data() {
+        let records = [make_record(0x3EE3C7, 0, b"SG", b"", b"", b"D-0816")];
+        let mut data = make_file_with_padding(&records, 4);
+        data.extend_from_slice(&[0xAA; 10]);
+        assert_eq!(detect_padding(&data), Some(4));
+
+        assert_eq!(
+            detect_padding(&make_valid_file(&records)),
+            Some(PADDING_SIZE)
+        );
+        assert_eq!(detect_padding(&make_valid_file(&[])), None);
+        assert_eq!(detect_padding(b""), None);
+    }
+
+    #[test]
+    fn decoding_all_works_for_concatenated_files() {
+        let mut data = make_valid_file(&[make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816")]);
+        data.extend_from_slice(&[0xff; 3]);
+        data.extend_from_slice(&make_valid_file(&[
+            make_record(0x123456, 0, b"", b"", b"", b"D-1234"),
+            make_record(0x654321, 0, b"", b"", b"", b"D-4321"),
+        ]));
+        data.extend_from_slice(&MAGIC);
+
+        let results = decode_all(&data);
+        assert_eq!(results.len(), 3);
+
+        let registrations = results[..2]
+            .iter()
+            .map(|result| {
+                let file = result.as_ref().unwrap();
+                file.records
+                    .iter()
+                    .map(|record| record.as_ref().unwrap().registration.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(registrations, [vec!["D-0816"], vec!["D-1234", "D-4321"]]);
+
+        assert_debug_snapshot!(results[2].as_ref().unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    fn decoding_all_works_for_empty_buffer() {
+        assert!(decode_all(b"").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decoding_timed_reports_stats() {
+        let data = make_valid_file(&[
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816"),
+            make_record(0x3EE3C8, 0, b"", b"", b"", b"\xff"),
+        ]);
+
+        let (file, stats) = decode_file_timed(&data).unwrap();
+        assert_eq!(file.records.len(), 2);
+        assert_eq!(stats.bytes, data.len());
+        assert_eq!(stats.records, 2);
+    }
+
+    #[test]
+    fn decoding_outcomes_keeps_raw_bytes() {
+        let valid = make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816");
+        let invalid = make_record(0x3EE3C8, 0, b"", b"", b"", b"\xff");
+        let data = make_valid_file(&[valid, invalid]);
+
+        let outcomes = decode_outcomes(&data, &DecodeOptions::default()).unwrap();
+        assert_eq!(outcomes.len(), 2);
+
+        assert_eq!(outcomes[0].index, 0);
+        assert_eq!(outcomes[0].raw, valid);
+        assert_eq!(outcomes[0].result.as_ref().unwrap().registration, "D-0816");
+
+        assert_eq!(outcomes[1].index, 1);
+        assert_eq!(outcomes[1].raw, invalid);
+        assert_debug_snapshot!(outcomes[1].result.as_ref().unwrap_err(), @r###"
+        InvalidUtf8 {
+            field: "registration",
+            offset: 80,
+        }
+        "###);
+    }
+
+    #[test]
+    fn decoding_works_with_larger_records() {
+        let records = [
+            make_record(0x000001, 0, b"", b"", b"", b"D-0001"),
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816"),
+        ];
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&1u32.to_le_bytes()); // version
+        data.extend_from_slice(&2u32.to_le_bytes()); // record count
+        for record in &records {
+            data.extend_from_slice(&record[0..4]);
+        }
+        data.extend_from_slice(&[0; PADDING_SIZE]);
+        for record in &records {
+            data.extend_from_slice(record);
+            data.extend_from_slice(&[0xff; 32]);
+        }
+
+        assert_eq!(infer_record_size(&data), Some(128));
+        assert_eq!(infer_record_size(&data[..data.len() - 1]), None);
+        assert_eq!(
+            infer_record_size(&make_valid_file(&records)),
+            Some(RECORD_SIZE)
+        );
+
+        let options = DecodeOptions {
+            record_size: Some(128),
+            ..Default::default()
+        };
+        let file = decode_file_with(&data, &options).unwrap();
+        let registrations = file
+            .records
+            .iter()
+            .map(|record| record.as_ref().unwrap().registration.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(registrations, ["D-0001", "D-0816"]);
+
+        let options = DecodeOptions {
+            record_size: Some(64),
+            ..Default::default()
+        };
+        assert_debug_snapshot!(decode_file_with(&data, &options).unwrap_err(), @r###"
+        InvalidRecordSize(
+            64,
+        )
+        "###);
+    }
+
+    #[test]
+    fn decoding_infers_record_count() {
+        let mut data = make_valid_file(&[]);
+        for id in [0x000001, 0x3EE3C7] {
+            data.extend_from_slice(&make_record(id, 0, b"", b"", b"", b"D-0816"));
+        }
+        // incomplete trailing record
+        data.extend_from_slice(&[0; 10]);
+
+        assert_eq!(decode_file(&data).unwrap().records.len(), 0);
+
+        let options = DecodeOptions {
+            trust_header_count: false,
+            ..Default::default()
+        };
+        let file = decode_file_with(&data, &options).unwrap();
+        assert!(file.index.is_empty());
+        let ids = file
+            .records
+            .iter()
+            .map(|record| record.as_ref().unwrap().flarm_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["000001", "3EE3C7"]);
+    }
+
+    #[test]
+    fn decoding_keeps_raw_frequency() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 123500, b"", b"", b"", b""),
+            make_record(0x000002, 0, b"", b"", b"", b""),
+        ]);
+        let options = DecodeOptions {
+            raw_frequency: true,
+            ..Default::default()
+        };
+        let file = decode_file_with(&data, &options).unwrap();
+        assert_eq!(file.records[0].as_ref().unwrap().frequency, "123500");
+        assert_eq!(file.records[1].as_ref().unwrap().frequency, "");
+    }
+
+    #[test]
+    fn decoding_rejects_trailing_data() {
+        let mut data = make_valid_file(&[make_record(0x3EE3C7, 0, b"", b"", b"", b"")]);
+        let options = DecodeOptions {
+            exact_length: true,
+            ..Default::default()
+        };
+        assert!(decode_file_with(&data, &options).is_ok());
+
+        data.extend_from_slice(b"foo");
+        assert!(decode_file(&data).is_ok());
+        assert_debug_snapshot!(decode_file_with(&data, &options).unwrap_err(), @r###"
+        TrailingData {
+            extra: 3,
+        }
+        "###);
+    }
+
+    #[test]
+    fn decoding_reports_trailing_bytes() {
+        let mut data = make_valid_file(&[make_record(0x000001, 0, b"", b"", b"", b"D-0816")]);
+        assert_eq!(decode_file(&data).unwrap().trailing_bytes, 0);
+
+        // appended record without updating the header
+        data.extend_from_slice(&make_record(0x3EE3C7, 0, b"", b"", b"", b"D-2188"));
+        data.extend_from_slice(b"junk");
+
+        let file = decode_file(&data).unwrap();
+        assert_eq!(file.trailing_bytes, RECORD_SIZE + 4);
+        assert_eq!(file.records.len(), 1);
+        #[cfg(feature = "parallel")]
+        assert_eq!(
+            decode_file_parallel(&data).unwrap().trailing_bytes,
+            RECORD_SIZE + 4
+        );
+    }
+
+    #[test]
+    fn decoding_with_filter_keeps_matching_records() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 0, b"", b"EDKA", b"LS6a", b"D-0816"),
+            make_record(0x1000000, 0, b"", b"EDKB", b"", b""),
+            make_record(0x000002, 0, b"", b"LFOB", b"ASK 21", b"F-CABC"),
+            make_record(0x000003, 0, b"", b"EDLE", b"Discus", b"D-2188"),
+        ]);
+
+        let decoded = decode_file_with_filter(&data, |mut record| {
+            record.plane_type.clear();
+            record.airfield.starts_with("ED").then_some(record)
+        })
+        .unwrap();
+        assert_eq!(decoded.index.len(), 4);
+        assert_debug_snapshot!(decoded.records, @r###"
+        [
+            Ok(
+                Record {
+                    flarm_id: "000001",
+                    pilot_name: "",
+                    airfield: "EDKA",
+                    plane_type: "",
+                    registration: "D-0816",
+                    call_sign: "",
+                    frequency: "",
+                    address_type: 0,
+                    reserved: [
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                    position: None,
+                },
+            ),
+            Err(
+                InvalidFlarmId(
+                    16777216,
+                ),
+            ),
+            Ok(
+                Record {
+                    flarm_id: "000003",
+                    pilot_name: "",
+                    airfield: "EDLE",
+                    plane_type: "",
+                    registration: "D-2188",
+                    call_sign: "",
+                    frequency: "",
+                    address_type: 0,
+                    reserved: [
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                    position: None,
+                },
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn decoding_trims_fields() {
+        let data = make_valid_file(&[make_record(
+            0x3EE3C7,
+            0,
+            b"SG\r\n",
+            b" EDKA\t",
+            b"LS 6a \x01",
+            b"D-0816   ",
+        )]);
+
+        let file = decode_file(&data).unwrap();
+        assert_eq!(file.records[0].as_ref().unwrap().registration, "D-0816   ");
+
+        let options = DecodeOptions::builder().trim_fields(true).build();
+        let file = decode_file_with(&data, &options).unwrap();
+        let record = file.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "SG");
+        assert_eq!(record.airfield, " EDKA");
+        assert_eq!(record.plane_type, "LS 6a");
+        assert_eq!(record.registration, "D-0816");
+    }
+
+    #[test]
+    fn decoding_normalizes_placeholders() {
+        let data = make_valid_file(&[make_record(0x3EE3C7, 0, b"ukn", b"   ", b"LS6a", b" - ")]);
+
+        let options = DecodeOptions::builder()
+            .normalize_placeholders(true)
+            .build();
+        let file = decode_file_with(&data, &options).unwrap();
+        let record = file.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "");
+        assert_eq!(record.airfield, "");
+        assert_eq!(record.plane_type, "LS6a");
+        assert_eq!(record.registration, "");
+
+        let options = DecodeOptions::builder()
+            .normalize_placeholders(true)
+            .placeholders(["LS6A"])
+            .build();
+        let file = decode_file_with(&data, &options).unwrap();
+        let record = file.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "ukn");
+        assert_eq!(record.airfield, "");
+        assert_eq!(record.plane_type, "");
+        assert_eq!(record.registration, " - ");
+    }
+
+    #[test]
+    fn decoding_folds_case() {
+        let data = make_valid_file(&[make_record(0x3EE3C7, 0, b"SG", b"EDKA", b"LS6a", b"D-0816")]);
+        let options = DecodeOptions {
+            case_fold: true,
+            ..Default::default()
+        };
+        let file = decode_file_with(&data, &options).unwrap();
+        let record = file.records[0].as_ref().unwrap();
+        assert_eq!(record.flarm_id, "3EE3C7");
+        assert_eq!(record.call_sign, "sg");
+        assert_eq!(record.airfield, "edka");
+        assert_eq!(record.plane_type, "ls6a");
+        assert_eq!(record.registration, "d-0816");
+    }
+
+    #[test]
+    fn field_offsets_works() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 0, b"", b"", b"", b""),
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816"),
+        ]);
+
+        let offsets = field_offsets(&data, "3EE3C7").unwrap().unwrap();
+        assert_eq!(
+            offsets.record,
+            HEADER_SIZE + 2 * INDEX_ENTRY_SIZE + PADDING_SIZE + RECORD_SIZE
+        );
+        assert_eq!(
+            &data[offsets.registration..offsets.registration + 6],
+            b"D-0816"
+        );
+        assert_debug_snapshot!(field_offsets(&data, "xyz").unwrap_err(), @r###"
+        MalformedFlarmId(
+            "xyz",
+        )
+        "###);
+        assert_debug_snapshot!(field_offsets(&data, "1000000").unwrap_err(), @r###"
+        MalformedFlarmId(
+            "1000000",
+        )
+        "###);
+        assert_debug_snapshot!(field_offsets(&data[..40], "3EE3C7").unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn decoded_file_serializes_errors_as_messages() {
+        let file = DecodedFile {
+            version: 1,
+            index: vec![1, 0x1000000],
+            records: vec![
+                Ok(Record {
+                    flarm_id: "000001".to_string(),
+                    ..Default::default()
+                }),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+            ],
+            trailing_bytes: 0,
+        };
+
+        assert_snapshot!(serde_json::to_string_pretty(&file).unwrap(), @r###"
+        {
+          "version": 1,
+          "index": [
+            1,
+            16777216
+          ],
+          "records": [
+            {
+              "Ok": {
+                "flarm_id": "000001",
+                "pilot_name": "",
+                "airfield": "",
+                "plane_type": "",
+                "registration": "",
+                "call_sign": "",
+                "frequency": "",
+                "address_type": 0,
+                "reserved": [
+                  0,
+                  0,
+                  0,
+                  0,
+                  0,
+                  0,
+                  0,
+                  0
+                ],
+                "position": null
+              }
+            },
+            {
+              "Err": "invalid FLARM id: 16777216"
+            }
+          ],
+          "trailing_bytes": 0
+        }
+        "###);
+    }
+
+    #[test]
+    fn iter_records_matches_decode_file() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 123500, b"AB", b"EDKA", b"LS6a", b"D-0816"),
+            make_record(0x1000000, 0, b"", b"", b"", b""),
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-2188"),
+        ]);
+
+        let records = iter_records(&data).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records.collect::<Vec<_>>(),
+            decode_file(&data).unwrap().records
+        );
+
+        let mut records = iter_records(&data).unwrap().skip(2);
+        assert_eq!(records.next().unwrap().unwrap().registration, "D-2188");
+        assert!(records.next().is_none());
+
+        assert_debug_snapshot!(iter_records(&data[..40]).unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_decoding_matches_decode_file() {
+        let records = (0..10_000u32)
+            .map(|i| {
+                let registration = format!("D-{i:04}");
+                let flarm_id = if i % 1000 == 999 { 0x1000000 + i } else { i };
+                make_record(flarm_id, 123500 + i, b"", b"", b"", registration.as_bytes())
+            })
+            .collect::<Vec<_>>();
+        let data = make_valid_file(&records);
+
+        let file = decode_file_parallel(&data).unwrap();
+        let expected = decode_file(&data).unwrap();
+        assert_eq!(file.index, expected.index);
+        assert_eq!(file.records, expected.records);
+        assert_eq!(file.records[1234].as_ref().unwrap().registration, "D-1234");
+
+        assert_debug_snapshot!(decode_file_parallel(&data[..data.len() - 1]).unwrap_err(), @"UnexpectedEof");
+        assert!(
+            decode_file_parallel(&make_valid_file(&[]))
+                .unwrap()
+                .records
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn decoding_strict_flarm_id_rejects_zero() {
+        let data = make_valid_file(&[
+            make_record(0x000000, 0, b"", b"", b"", b""),
+            make_record(0x000001, 0, b"", b"", b"", b""),
+        ]);
+
+        let file = decode_file(&data).unwrap();
+        assert_eq!(file.records[0].as_ref().unwrap().flarm_id, "000000");
+
+        let options = DecodeOptions::builder().strict_flarm_id(true).build();
+        let file = decode_file_with(&data, &options).unwrap();
+        assert_eq!(file.records[0], Err(DecodeError::InvalidFlarmId(0)));
+        assert_eq!(file.records[1].as_ref().unwrap().flarm_id, "000001");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decoding_records_positions() {
+        let data = make_valid_file(&[
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816"),
+            make_record(0x000002, 0, b"", b"", b"", b"D-2188"),
+            make_record(0x000001, 0, b"", b"", b"", b"D-9527"),
+        ]);
+
+        let file = decode_file(&data).unwrap();
+        assert_eq!(file.records[0].as_ref().unwrap().position, None);
+
+        let options = DecodeOptions::builder().record_positions(true).build();
+        let records = |file: DecodedFile| file.records.into_iter().map(Result::unwrap);
+        let mut file = crate::File {
+            version: 1,
+            records: records(decode_file_with(&data, &options).unwrap()).collect(),
+        };
+        let encoded = crate::tdb::encode_file(&file).unwrap();
+        file.records[1].pilot_name = "Tobias Bieniek".to_string();
+        let edited = crate::tdb::encode_file(&file).unwrap();
+
+        let index = decode_file(&edited).unwrap().index;
+        assert_eq!(index, decode_file(&encoded).unwrap().index);
+        assert_eq!(index, [0x000001, 0x000002, 0x3EE3C7]);
+
+        let ids = |records: Vec<&crate::Record>| {
+            records
+                .into_iter()
+                .map(|record| record.flarm_id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(file.in_original_order()),
+            ["3EE3C7", "000002", "000001"]
+        );
+        assert_eq!(
+            ids(file.sorted_by_flarm_id()),
+            ["000001", "000002", "3EE3C7"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decoding_formats_flarm_id_case() {
+        let data = make_valid_file(&[make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816")]);
+
+        let upper = decode_file(&data).unwrap();
+        assert_eq!(upper.records[0].as_ref().unwrap().flarm_id, "3EE3C7");
+
+        let options = DecodeOptions::builder()
+            .flarm_id_case(FlarmIdCase::Lower)
+            .build();
+        let lower = decode_file_with(&data, &options).unwrap();
+        assert_eq!(lower.records[0].as_ref().unwrap().flarm_id, "3ee3c7");
+
+        let records = |file: DecodedFile| file.records.into_iter().map(Result::unwrap);
+        let lower = crate::File {
+            version: 1,
+            records: records(lower).collect(),
+        };
+        assert_eq!(crate::tdb::encode_file(&lower).unwrap(), data);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn decoding_keeps_reserved_bytes() {
+        let mut record = make_record(0x3EE3C7, 123500, b"SG", b"EDKA", b"LS6a", b"D-0816");
+        record[RESERVED_OFFSET..RESERVED_OFFSET + RESERVED_SIZE].copy_from_slice(b"\x01reserve");
+        let data = make_valid_file(&[record]);
+
+        let file = decode_file(&data).unwrap();
+        let decoded = file.records[0].as_ref().unwrap();
+        assert_eq!(&decoded.reserved, b"\x01reserve");
+
+        let file = crate::File {
+            version: file.version,
+            records: vec![decoded.clone()],
+        };
+        assert_eq!(crate::tdb::encode_file(&file).unwrap(), data);
+    }
+
+    #[test]
+    fn decoding_replaces_invalid_utf8_with_lossy_utf8() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 0, b"AB", b"M\xFCnchen", b"LS6a", b"D-0816"),
+            make_record(0x000002, 0, b"", b"EDKA", b"", b"D-2188"),
+        ]);
+        assert!(decode_file(&data).unwrap().records[0].is_err());
+
+        let options = DecodeOptions::builder().lossy_utf8(true).build();
+        let outcomes = decode_outcomes(&data, &options).unwrap();
+        let record = outcomes[0].result.as_ref().unwrap();
+        assert_eq!(record.airfield, "M\u{FFFD}nchen");
+        assert_eq!(record.registration, "D-0816");
+        assert_eq!(outcomes[0].lossy_fields, ["airfield"]);
+        assert!(outcomes[1].lossy_fields.is_empty());
+
+        let file = decode_file_with(&data, &options).unwrap();
+        assert_eq!(file.records[0].as_ref().unwrap(), record);
+    }
+
+    #[test]
+    fn decoding_verifies_index() {
+        let options = DecodeOptions::builder().verify_index(true).build();
+
+        let records = [
+            make_record(0x000002, 0, b"", b"", b"", b""),
+            make_record(0x000001, 0, b"", b"", b"", b""),
+        ];
+        let mut data = make_valid_file(&records);
+        assert!(decode_file(&data).is_ok());
+        assert_debug_snapshot!(decode_file_with(&data, &options).unwrap_err(), @r###"
+        UnsortedIndex {
+            position: 1,
+        }
+        "###);
+
+        data[HEADER_SIZE + INDEX_ENTRY_SIZE] = 0x03;
+        assert!(decode_file(&data).is_ok());
+        assert_debug_snapshot!(decode_file_with(&data, &options).unwrap_err(), @r###"
+        IndexMismatch {
+            index: 3,
+            record: 1,
+            position: 1,
+        }
+        "###);
+
+        let data = make_valid_file(&[records[1], records[0]]);
+        assert!(decode_file_with(&data, &options).is_ok());
+    }
+
+    #[test]
+    fn lookup_uses_index() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 0, b"", b"", b"", b"D-0001"),
+            make_record(0x000010, 0, b"", b"", b"", b"D-0010"),
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-0816"),
+        ]);
+
+        for (flarm_id, registration) in
+            [("000001", "D-0001"), ("10", "D-0010"), ("3ee3c7", "D-0816")]
+        {
+            let record = lookup(&data, flarm_id).unwrap().unwrap();
+            assert_eq!(record.registration, registration);
+        }
+        assert_eq!(lookup(&data, "000002").unwrap(), None);
+        assert_eq!(lookup(&make_valid_file(&[]), "000002").unwrap(), None);
+
+        assert_debug_snapshot!(lookup(&data, "1000000").unwrap_err(), @r###"
+        MalformedFlarmId(
+            "1000000",
+        )
+        "###);
+        assert_debug_snapshot!(lookup(&data[..40], "000001").unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    fn decoding_record_range_works_for_truncated_file() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 0, b"", b"", b"", b"D-0001"),
+            make_record(0x000002, 0, b"", b"", b"", b"D-0002"),
+            make_record(0x000003, 0, b"", b"", b"", b"D-0003"),
+        ]);
+        let data = &data[..data.len() - 10];
+        assert!(decode_file(data).is_err());
+
+        let records = decode_record_range(data, 1, 10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].as_ref().unwrap().registration, "D-0002");
+        assert_debug_snapshot!(records[1].as_ref().unwrap_err(), @"UnexpectedEof");
+
+        assert!(decode_record_range(data, 2, 1).unwrap().is_empty());
+        assert_debug_snapshot!(decode_record_range(&data[..4], 0, 1).unwrap_err(), @"UnexpectedEof");
+    }
+
     #[test]
     fn decoding_reports_invalid_flarm_id() {
         let mut record = [0u8; RECORD_SIZE];
@@ -261,6 +2033,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decoding_masks_flarm_id() {
+        let mut record = [0u8; RECORD_SIZE];
+        record[0..4].copy_from_slice(&0x01DD1234u32.to_le_bytes());
+        let data = make_valid_file(&[record]);
+        let options = DecodeOptions {
+            mask_flarm_id: true,
+            ..Default::default()
+        };
+        let result = decode_file_with(&data, &options).unwrap();
+        let record = result.records[0].as_ref().unwrap();
+        assert_eq!(record.flarm_id, "DD1234");
+        assert_eq!(record.address_type, 1);
+    }
+
     #[test]
     fn decoding_reports_invalid_utf8() {
         let mut record = make_record(0x000001, 0, b"", b"", b"", b"");
@@ -279,4 +2066,78 @@ mod tests {
         "###
         );
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader_matches_decode_file() {
+        let data = make_valid_file(&[
+            make_record(0x000001, 123500, b"AB", b"EDKA", b"LS6a", b"D-0816"),
+            make_record(0x1000000, 0, b"", b"", b"", b""),
+            make_record(0x3EE3C7, 0, b"", b"", b"", b"D-2188"),
+        ]);
+        let expected = decode_file(&data).unwrap();
+
+        let mut reader = Reader::new(data.as_slice());
+        assert_eq!(reader.read_header().unwrap(), (1, 3));
+        let mut records = Vec::new();
+        while let Some(record) = reader.read_record().unwrap() {
+            records.push(record);
+        }
+        assert_eq!(records, expected.records);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader_reads_until_eof_without_header_count() {
+        let mut data = make_file_with_padding(&[], PADDING_SIZE);
+        data.extend_from_slice(&make_record(0x000001, 0, b"", b"", b"", b""));
+
+        let options = DecodeOptions {
+            trust_header_count: false,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(data.as_slice(), options);
+        assert_eq!(reader.read_header().unwrap(), (1, 0));
+        assert!(reader.read_record().unwrap().unwrap().is_ok());
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader_fails_for_truncated_record() {
+        let data = make_valid_file(&[make_record(0x000001, 0, b"", b"", b"", b"")]);
+
+        let mut reader = Reader::new(&data[..data.len() - 1]);
+        assert_debug_snapshot!(reader.read_record().unwrap_err(), @"UnexpectedEof");
+
+        let mut reader = Reader::new(&data[..HEADER_SIZE + 2]);
+        assert_debug_snapshot!(reader.read_header().unwrap_err(), @"UnexpectedEof");
+
+        let mut data = make_valid_file(&[]);
+        data.extend_from_slice(&[0; RECORD_SIZE - 1]);
+        let options = DecodeOptions {
+            trust_header_count: false,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(data.as_slice(), options);
+        assert_debug_snapshot!(reader.read_record().unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn reader_fails_for_trailing_data_with_exact_length() {
+        let mut data = make_valid_file(&[]);
+        data.extend_from_slice(b"abc");
+
+        let options = DecodeOptions {
+            exact_length: true,
+            ..Default::default()
+        };
+        let mut reader = Reader::with_options(data.as_slice(), options);
+        assert_debug_snapshot!(reader.read_record().unwrap_err(), @r###"
+        TrailingData {
+            extra: 3,
+        }
+        "###);
+    }
 }
@@ -0,0 +1,822 @@
+use super::consts::STRING_FIELD_SIZE;
+use crate::tdb::{
+    DecodeError, DecodeOptions, DecodedFile, EncodeError, parse_flarm_id, parse_frequency,
+};
+use crate::{File, Record};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// How often the values of the most repetitive fields occur in a file, see
+/// [DecodedFile::redundancy_report].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedundancyReport {
+    /// Number of successfully decoded records.
+    pub records: usize,
+    /// Number of records per non-empty airfield.
+    pub airfields: BTreeMap<String, usize>,
+    /// Number of records per non-empty plane type.
+    pub plane_types: BTreeMap<String, usize>,
+}
+
+impl RedundancyReport {
+    /// Returns the number of airfield and plane type values that repeat a
+    /// value of an earlier record, which can be used as a rough estimate
+    /// for how well the file compresses.
+    pub fn repeated_values(&self) -> usize {
+        self.airfields
+            .values()
+            .chain(self.plane_types.values())
+            .map(|count| count - 1)
+            .sum()
+    }
+}
+
+/// Record counts of a file, see [DecodedFile::stats].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordStats {
+    /// Number of records, including records that failed to decode.
+    pub records: usize,
+    /// Number of successfully decoded records.
+    pub ok: usize,
+    /// Number of records that failed to decode.
+    pub errors: usize,
+    /// Number of successfully decoded records without a frequency.
+    pub empty_frequency: usize,
+    /// Number of successfully decoded records with a registration.
+    pub with_registration: usize,
+    /// Number of errors per [DecodeError] variant, e.g. `"InvalidUtf8"`.
+    pub error_kinds: BTreeMap<&'static str, usize>,
+}
+
+/// What [DecodedFile::into_clean_file] removed from a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanReport {
+    /// Number of records that failed to decode and were dropped.
+    pub invalid: usize,
+    /// Number of records that were merged into an earlier record with the
+    /// same FLARM id.
+    pub merged: usize,
+}
+
+impl DecodedFile {
+    /// Returns the successfully decoded records sorted by pilot surname, for
+    /// printing rosters.
+    ///
+    /// The surname is taken to be the last whitespace-separated word of
+    /// [Record::pilot_name] and is compared case-insensitively. Records
+    /// without a pilot name are sorted last. Ties are broken by
+    /// registration.
+    pub fn sorted_by_pilot(&self) -> Vec<&Record> {
+        let mut records = self
+            .records
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|record| (surname(&record.pilot_name), record))
+            .collect::<Vec<_>>();
+
+        records.sort_by(|(a_name, a), (b_name, b)| {
+            compare_surnames(a_name.as_deref(), b_name.as_deref())
+                .then_with(|| a.registration.cmp(&b.registration))
+        });
+
+        records.into_iter().map(|(_, record)| record).collect()
+    }
+
+    /// Returns the distinct non-empty airfields of the successfully decoded
+    /// records, in sorted order.
+    pub fn airfields(&self) -> BTreeSet<String> {
+        self.records
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .filter(|record| !record.airfield.is_empty())
+            .map(|record| record.airfield.clone())
+            .collect()
+    }
+
+    /// Returns the `n` most common non-empty values of the given field among
+    /// the successfully decoded records, together with their number of
+    /// occurrences.
+    ///
+    /// Values with the same count are sorted alphabetically. Unknown fields
+    /// result in an empty list, see [Record::field] for the field names.
+    pub fn top_values(&self, field: &str, n: usize) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for record in self
+            .records
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+        {
+            match record.field(field) {
+                Some("") => {}
+                Some(value) => *counts.entry(value).or_default() += 1,
+                None => return Vec::new(),
+            }
+        }
+
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+
+        counts
+            .into_iter()
+            .take(n)
+            .map(|(value, count)| (value.to_string(), count))
+            .collect()
+    }
+
+    /// Counts how many successfully decoded records share each airfield and
+    /// plane type.
+    pub fn redundancy_report(&self) -> RedundancyReport {
+        let mut report = RedundancyReport::default();
+        for record in self
+            .records
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+        {
+            report.records += 1;
+            if !record.airfield.is_empty() {
+                *report.airfields.entry(record.airfield.clone()).or_default() += 1;
+            }
+            if !record.plane_type.is_empty() {
+                *report
+                    .plane_types
+                    .entry(record.plane_type.clone())
+                    .or_default() += 1;
+            }
+        }
+
+        report
+    }
+
+    /// Counts the valid and invalid records, and how many of the valid
+    /// records have a frequency and a registration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// # use flarmnet::tdb::{DecodeError, DecodedFile};
+    /// let decoded = DecodedFile {
+    ///     version: 1,
+    ///     index: vec![],
+    ///     records: vec![Ok(Record::default()), Err(DecodeError::InvalidFlarmId(0x1000000))],
+    ///     trailing_bytes: 0,
+    /// };
+    ///
+    /// let stats = decoded.stats();
+    /// assert_eq!((stats.ok, stats.errors), (1, 1));
+    /// assert_eq!(stats.error_kinds["InvalidFlarmId"], 1);
+    /// ```
+    pub fn stats(&self) -> RecordStats {
+        let mut stats = RecordStats {
+            records: self.records.len(),
+            ..Default::default()
+        };
+        for result in &self.records {
+            match result {
+                Ok(record) => {
+                    stats.ok += 1;
+                    if record.frequency.is_empty() {
+                        stats.empty_frequency += 1;
+                    }
+                    if !record.registration.is_empty() {
+                        stats.with_registration += 1;
+                    }
+                }
+                Err(error) => {
+                    stats.errors += 1;
+                    *stats.error_kinds.entry(error_kind(error)).or_default() += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Returns the records on the given zero-based page, with `per_page`
+    /// records per page.
+    ///
+    /// The last page may contain fewer records, and pages past the end (or
+    /// any page if `per_page` is zero) are empty.
+    pub fn page(&self, page: usize, per_page: usize) -> &[Result<Record, DecodeError>] {
+        let len = self.records.len();
+        let start = page.saturating_mul(per_page).min(len);
+        let end = start.saturating_add(per_page).min(len);
+        &self.records[start..end]
+    }
+
+    /// Converts the decoded file into a [File] of the successfully decoded
+    /// records, and returns the errors of the other records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// # use flarmnet::tdb::{DecodeError, DecodedFile};
+    /// let decoded = DecodedFile {
+    ///     version: 1,
+    ///     index: vec![],
+    ///     records: vec![Ok(Record::default()), Err(DecodeError::InvalidFlarmId(0x1000000))],
+    ///     trailing_bytes: 0,
+    /// };
+    ///
+    /// let (file, errors) = decoded.into_file();
+    /// assert_eq!(file.records, vec![Record::default()]);
+    /// assert_eq!(errors, vec![DecodeError::InvalidFlarmId(0x1000000)]);
+    /// ```
+    pub fn into_file(self) -> (File, Vec<DecodeError>) {
+        let mut records = Vec::with_capacity(self.records.len());
+        let mut errors = Vec::new();
+        for result in self.records {
+            match result {
+                Ok(record) => records.push(record),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let file = File {
+            version: self.version,
+            records,
+        };
+        (file, errors)
+    }
+
+    /// Converts the decoded file into a [File], or returns the error of the
+    /// first record that failed to decode.
+    pub fn try_into_file(self) -> Result<File, DecodeError> {
+        Ok(File {
+            version: self.version,
+            records: self.records.into_iter().collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Converts the decoded file into a [File] that can be encoded again
+    /// without errors or truncation.
+    ///
+    /// Records that failed to decode or whose FLARM id can not be encoded
+    /// are dropped. The FLARM id is written as six uppercase hex digits,
+    /// surrounding whitespace is trimmed from all text fields, text fields
+    /// are shortened to the TDB byte limit at a character boundary, and
+    /// frequencies are normalized to MHz with three decimals. A frequency
+    /// that can not be encoded is cleared. The records are sorted by FLARM
+    /// id, in the same order the encoder writes them.
+    ///
+    /// The frequencies are expected in MHz, use
+    /// [DecodedFile::to_canonical_file_with] for files that were decoded
+    /// with [DecodeOptions::raw_frequency].
+    pub fn to_canonical_file(self) -> File {
+        self.to_canonical_file_with(&DecodeOptions::default())
+    }
+
+    /// Converts the decoded file into a [File] like
+    /// [DecodedFile::to_canonical_file], reading the frequencies in kHz if
+    /// the file was decoded with [DecodeOptions::raw_frequency].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// # use flarmnet::tdb::{DecodeOptions, DecodedFile};
+    /// let decoded = DecodedFile {
+    ///     version: 1,
+    ///     index: vec![],
+    ///     records: vec![Ok(Record {
+    ///         flarm_id: "3EE3C7".to_string(),
+    ///         frequency: "130530".to_string(),
+    ///         ..Default::default()
+    ///     })],
+    ///     trailing_bytes: 0,
+    /// };
+    ///
+    /// let options = DecodeOptions::builder().raw_frequency(true).build();
+    /// let file = decoded.to_canonical_file_with(&options);
+    /// assert_eq!(file.records[0].frequency, "130.530");
+    /// ```
+    pub fn to_canonical_file_with(self, options: &DecodeOptions) -> File {
+        let mut records: Vec<(u32, Record)> = self
+            .records
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter_map(|record| canonicalize(record, options.raw_frequency))
+            .collect();
+        records.sort_by_key(|(id, _)| *id);
+
+        File {
+            version: self.version,
+            records: records.into_iter().map(|(_, record)| record).collect(),
+        }
+    }
+
+    /// Converts the decoded file into a sorted [File] without duplicates
+    /// that is ready to be encoded.
+    ///
+    /// The records are canonicalized like [DecodedFile::to_canonical_file]
+    /// does, and records with the same FLARM id are combined with
+    /// [File::merge_fields]. Fails if a FLARM id can not be encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use flarmnet::Record;
+    /// # use flarmnet::tdb::{DecodeError, DecodedFile};
+    /// let record = |pilot_name: &str| Record {
+    ///     flarm_id: "3EE3C7".to_string(),
+    ///     pilot_name: pilot_name.to_string(),
+    ///     ..Default::default()
+    /// };
+    /// let decoded = DecodedFile {
+    ///     version: 1,
+    ///     index: vec![],
+    ///     records: vec![
+    ///         Ok(record("")),
+    ///         Err(DecodeError::InvalidFlarmId(0x1000000)),
+    ///         Ok(record("Tobias Bieniek")),
+    ///     ],
+    ///     trailing_bytes: 0,
+    /// };
+    ///
+    /// let (file, report) = decoded.into_clean_file().unwrap();
+    /// assert_eq!(file.records, vec![record("Tobias Bieniek")]);
+    /// assert_eq!((report.invalid, report.merged), (1, 1));
+    /// ```
+    pub fn into_clean_file(self) -> Result<(File, CleanReport), EncodeError> {
+        let invalid = self.records.iter().filter(|result| result.is_err()).count();
+
+        // the canonical file drops these records, so they are checked first
+        for (i, record) in self.records.iter().enumerate() {
+            if let Ok(record) = record {
+                parse_flarm_id(record.flarm_id.trim()).map_err(|error| error.with_index(i))?;
+            }
+        }
+
+        let mut file = self.to_canonical_file();
+        let records = file.records.len();
+        file.merge_fields();
+
+        let report = CleanReport {
+            invalid,
+            merged: records - file.records.len(),
+        };
+        Ok((file, report))
+    }
+}
+
+/// Returns the parsed FLARM id and the canonical record, or `None` if the
+/// FLARM id can not be encoded.
+fn canonicalize(record: Record, raw_frequency: bool) -> Option<(u32, Record)> {
+    let id = parse_flarm_id(record.flarm_id.trim()).ok()?;
+
+    // zero is stored for an empty frequency, so it is cleared as well
+    let frequency = match parse_frequency(record.frequency.trim(), raw_frequency) {
+        Ok(Some(khz)) if khz != 0 => format!("{}.{:03}", khz / 1000, khz % 1000),
+        _ => String::new(),
+    };
+
+    let record = Record {
+        flarm_id: format!("{id:06X}"),
+        pilot_name: canonical_text(&record.pilot_name),
+        airfield: canonical_text(&record.airfield),
+        plane_type: canonical_text(&record.plane_type),
+        registration: canonical_text(&record.registration),
+        call_sign: canonical_text(&record.call_sign),
+        frequency,
+        ..record
+    };
+    Some((id, record))
+}
+
+fn canonical_text(value: &str) -> String {
+    let value = value.trim();
+    let value = &value[..value.floor_char_boundary(STRING_FIELD_SIZE - 1)];
+    value.trim_end().to_string()
+}
+
+fn surname(pilot_name: &str) -> Option<String> {
+    pilot_name.split_whitespace().last().map(str::to_lowercase)
+}
+
+fn error_kind(error: &DecodeError) -> &'static str {
+    match error {
+        DecodeError::UnexpectedEof => "UnexpectedEof",
+        DecodeError::InvalidMagic(_) => "InvalidMagic",
+        DecodeError::InvalidFlarmId(_) => "InvalidFlarmId",
+        DecodeError::InvalidUtf8 { .. } => "InvalidUtf8",
+        DecodeError::InvalidDeltaOperation(_) => "InvalidDeltaOperation",
+        DecodeError::InvalidRecordSize(_) => "InvalidRecordSize",
+        DecodeError::TrailingData { .. } => "TrailingData",
+        DecodeError::Io(_) => "Io",
+        DecodeError::MalformedFlarmId(_) => "MalformedFlarmId",
+        DecodeError::IndexMismatch { .. } => "IndexMismatch",
+        DecodeError::UnsortedIndex { .. } => "UnsortedIndex",
+    }
+}
+
+fn compare_surnames(a: Option<&str>, b: Option<&str>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_debug_snapshot;
+
+    fn make_record(pilot_name: &str, registration: &str) -> Record {
+        Record {
+            pilot_name: pilot_name.to_string(),
+            registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stats_counts_records() {
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                Ok(make_record("Tobias Bieniek", "D-0816")),
+                Ok(Record {
+                    frequency: "123.500".to_string(),
+                    ..make_record("", "")
+                }),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                Err(DecodeError::InvalidUtf8 {
+                    field: "pilot_name",
+                    offset: 32,
+                }),
+                Err(DecodeError::InvalidFlarmId(0x2000000)),
+            ],
+            trailing_bytes: 0,
+        };
+
+        assert_debug_snapshot!(file.stats(), @r###"
+        RecordStats {
+            records: 5,
+            ok: 2,
+            errors: 3,
+            empty_frequency: 1,
+            with_registration: 1,
+            error_kinds: {
+                "InvalidFlarmId": 2,
+                "InvalidUtf8": 1,
+            },
+        }
+        "###);
+    }
+
+    #[test]
+    fn try_into_file_fails_for_first_error() {
+        let file = DecodedFile {
+            version: 3,
+            index: vec![],
+            records: vec![
+                Ok(make_record("", "D-0816")),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                Err(DecodeError::UnexpectedEof),
+            ],
+            trailing_bytes: 0,
+        };
+        assert_eq!(
+            file.clone().try_into_file().unwrap_err(),
+            DecodeError::InvalidFlarmId(0x1000000)
+        );
+
+        let (file, errors) = file.into_file();
+        assert_eq!(file.version, 3);
+        assert_eq!(file.records, vec![make_record("", "D-0816")]);
+        assert_eq!(errors.len(), 2);
+
+        let decoded = DecodedFile {
+            version: 3,
+            index: vec![],
+            records: vec![Ok(make_record("", "D-0816"))],
+            trailing_bytes: 0,
+        };
+        assert_eq!(decoded.try_into_file().unwrap(), file);
+    }
+
+    #[test]
+    fn into_clean_file_works() {
+        let record = |flarm_id: &str, pilot_name: &str, registration: &str| Record {
+            flarm_id: flarm_id.to_string(),
+            ..make_record(pilot_name, registration)
+        };
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                Ok(record("00000F", "Max Bieniek", "")),
+                Ok(record("000001", "", "D-2188")),
+                Err(DecodeError::InvalidUtf8 {
+                    field: "pilot_name",
+                    offset: 32,
+                }),
+                Ok(record("00000F", "Tobias Bieniek", "D-0816")),
+                Ok(record("000001", "", "D-9999")),
+            ],
+            trailing_bytes: 0,
+        };
+
+        let (file, report) = file.into_clean_file().unwrap();
+        assert_eq!(
+            report,
+            CleanReport {
+                invalid: 1,
+                merged: 2
+            }
+        );
+        assert_eq!(
+            file.records,
+            vec![
+                record("000001", "", "D-2188"),
+                record("00000F", "Max Bieniek", "D-0816"),
+            ]
+        );
+        assert!(crate::tdb::encode_file(&file).is_ok());
+
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![Ok(record("XYZ", "", ""))],
+            trailing_bytes: 0,
+        };
+        assert_debug_snapshot!(file.into_clean_file().unwrap_err(), @r###"
+        InvalidFlarmId {
+            value: "XYZ",
+            index: Some(
+                0,
+            ),
+        }
+        "###);
+    }
+
+    #[test]
+    fn sorted_by_pilot_works() {
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                Ok(make_record("Tobias Bieniek", "D-0816")),
+                Ok(make_record("", "D-1234")),
+                Ok(make_record("Anna  Zimmermann", "D-5555")),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                Ok(make_record("Max Bieniek", "D-0001")),
+                Ok(make_record("jan mustermann", "D-9999")),
+            ],
+            trailing_bytes: 0,
+        };
+
+        let registrations = file
+            .sorted_by_pilot()
+            .iter()
+            .map(|record| record.registration.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            registrations,
+            ["D-0001", "D-0816", "D-9999", "D-5555", "D-1234"]
+        );
+    }
+
+    #[test]
+    fn airfields_works() {
+        let airfield = |airfield: &str| {
+            Ok(Record {
+                airfield: airfield.to_string(),
+                ..Default::default()
+            })
+        };
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                airfield("EDKA"),
+                airfield(""),
+                airfield("EDLN"),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                airfield("EDKA"),
+            ],
+            trailing_bytes: 0,
+        };
+
+        assert_eq!(
+            file.airfields().into_iter().collect::<Vec<_>>(),
+            ["EDKA", "EDLN"]
+        );
+    }
+
+    #[test]
+    fn top_values_works() {
+        let plane_type = |plane_type: &str| {
+            Ok(Record {
+                plane_type: plane_type.to_string(),
+                ..Default::default()
+            })
+        };
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                plane_type("LS6a"),
+                plane_type("ASK 21"),
+                plane_type(""),
+                plane_type("Discus"),
+                plane_type("LS6a"),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                plane_type("ASK 21"),
+                plane_type(""),
+            ],
+            trailing_bytes: 0,
+        };
+
+        assert_eq!(
+            file.top_values("plane_type", 2),
+            [("ASK 21".to_string(), 2), ("LS6a".to_string(), 2)]
+        );
+        assert_eq!(file.top_values("plane_type", 5).len(), 3);
+        assert!(file.top_values("airfield", 5).is_empty());
+        assert!(file.top_values("foo", 5).is_empty());
+    }
+
+    #[test]
+    fn redundancy_report_works() {
+        let record = |airfield: &str, plane_type: &str| {
+            Ok(Record {
+                airfield: airfield.to_string(),
+                plane_type: plane_type.to_string(),
+                ..Default::default()
+            })
+        };
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                record("EDKA", "LS6a"),
+                record("EDKA", "ASK 21"),
+                record("", "LS6a"),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                record("EDLN", "LS6a"),
+            ],
+            trailing_bytes: 0,
+        };
+
+        let report = file.redundancy_report();
+        assert_debug_snapshot!(report, @r###"
+        RedundancyReport {
+            records: 4,
+            airfields: {
+                "EDKA": 2,
+                "EDLN": 1,
+            },
+            plane_types: {
+                "ASK 21": 1,
+                "LS6a": 3,
+            },
+        }
+        "###);
+        assert_eq!(report.repeated_values(), 3);
+    }
+
+    #[test]
+    fn page_works() {
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: (0..5)
+                .map(|i| Ok(make_record("", &i.to_string())))
+                .collect(),
+            trailing_bytes: 0,
+        };
+
+        let registrations = |page: &[Result<Record, DecodeError>]| {
+            page.iter()
+                .map(|record| record.as_ref().unwrap().registration.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(registrations(file.page(0, 2)), ["0", "1"]);
+        assert_eq!(registrations(file.page(2, 2)), ["4"]);
+        assert!(file.page(3, 2).is_empty());
+        assert!(file.page(0, 0).is_empty());
+        assert!(file.page(usize::MAX, usize::MAX).is_empty());
+    }
+
+    #[test]
+    fn to_canonical_file_normalizes_records() {
+        let file = DecodedFile {
+            version: 7,
+            index: vec![],
+            records: vec![
+                Ok(Record {
+                    flarm_id: "3ee3c7".to_string(),
+                    pilot_name: " Tobias Bieniek ".to_string(),
+                    plane_type: "Schempp-Hirth Discus".to_string(),
+                    frequency: "123.5".to_string(),
+                    ..make_record("", " D-0816")
+                }),
+                Err(DecodeError::InvalidFlarmId(0x1000000)),
+                Ok(Record {
+                    flarm_id: "000001".to_string(),
+                    frequency: "n/a".to_string(),
+                    ..make_record("", "D-2188")
+                }),
+            ],
+            trailing_bytes: 0,
+        };
+
+        let file = file.to_canonical_file();
+        assert_eq!(file.version, 7);
+        assert_debug_snapshot!(file.records, @r###"
+        [
+            Record {
+                flarm_id: "000001",
+                pilot_name: "",
+                airfield: "",
+                plane_type: "",
+                registration: "D-2188",
+                call_sign: "",
+                frequency: "",
+                address_type: 0,
+                reserved: [
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ],
+                position: None,
+            },
+            Record {
+                flarm_id: "3EE3C7",
+                pilot_name: "Tobias Bieniek",
+                airfield: "",
+                plane_type: "Schempp-Hirth D",
+                registration: "D-0816",
+                call_sign: "",
+                frequency: "123.500",
+                address_type: 0,
+                reserved: [
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ],
+                position: None,
+            },
+        ]
+        "###);
+
+        let encoded = crate::tdb::encode_file(&file).unwrap();
+        let decoded = crate::tdb::decode_file(&encoded).unwrap();
+        let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+        assert_eq!(records.unwrap(), file.records);
+    }
+
+    #[test]
+    fn to_canonical_file_with_raw_frequency_encodes() {
+        let record = |flarm_id: &str, frequency: &str| Record {
+            flarm_id: flarm_id.to_string(),
+            frequency: frequency.to_string(),
+            ..Default::default()
+        };
+        let file = DecodedFile {
+            version: 1,
+            index: vec![],
+            records: vec![
+                Ok(record("3EE3C7", "130530")),
+                Ok(record("a", "123.5")),
+                Ok(record("XYZ", "130530")),
+                Ok(record("1000000", "")),
+                Ok(record(" 1 ", "0")),
+            ],
+            trailing_bytes: 0,
+        };
+
+        let options = DecodeOptions::builder().raw_frequency(true).build();
+        let file = file.to_canonical_file_with(&options);
+        let records = file
+            .records
+            .iter()
+            .map(|record| (record.flarm_id.as_str(), record.frequency.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            records,
+            [("000001", ""), ("00000A", ""), ("3EE3C7", "130.530")]
+        );
+
+        let encoded = crate::tdb::encode_file(&file).unwrap();
+        let decoded = crate::tdb::decode_file(&encoded).unwrap();
+        let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+        assert_eq!(records.unwrap(), file.records);
+    }
+}
@@ -0,0 +1,262 @@
+//! Record-level changes between two versions of a TDB file.
+//!
+//! A binary delta starts with the magic bytes `TDBD`, followed by the version of the
+//! new file and the number of operations, both as little-endian `u32`. Each
+//! operation is a single type byte followed by its payload:
+//!
+//! - `0x01` (upsert): the new 96-byte record, replacing any records with the
+//!   same FLARM id
+//! - `0x02` (remove): the little-endian `u32` FLARM id of the records to remove
+
+use super::consts::*;
+use super::decode::{DecodeError, Layout, decode_file, record_flarm_id};
+use super::encode::{EncodeError, Writer};
+use super::options::DecodeOptions;
+use crate::{File, join};
+use std::collections::BTreeMap;
+
+const DELTA_MAGIC: [u8; 4] = *b"TDBD";
+const OP_UPSERT: u8 = 0x01;
+const OP_REMOVE: u8 = 0x02;
+
+/// Encodes the changes between two versions of a file as a binary delta,
+/// which can be applied to the encoded `old` file with [apply_binary_delta].
+///
+/// Records are matched by FLARM id. Only added and changed records are
+/// stored in full, removed records are stored as their FLARM id.
+pub fn encode_binary_delta(old: &File, new: &File) -> Result<Vec<u8>, EncodeError> {
+    let writer = Writer::new(std::io::sink());
+
+    let mut operations = Vec::new();
+    let mut count = 0u32;
+    for pair in join(old, new) {
+        match pair {
+            (Some(old_record), None) => {
+                operations.push(OP_REMOVE);
+                operations.extend_from_slice(&writer.flarm_id(old_record)?.to_le_bytes());
+            }
+            (old_record, Some(new_record)) if old_record != Some(new_record) => {
+                let id = writer.flarm_id(new_record)?;
+                operations.push(OP_UPSERT);
+                operations.extend_from_slice(&writer.record_data(id, new_record)?);
+            }
+            _ => continue,
+        }
+        count += 1;
+    }
+
+    let mut delta = Vec::with_capacity(HEADER_SIZE + operations.len());
+    delta.extend_from_slice(&DELTA_MAGIC);
+    delta.extend_from_slice(&new.version.to_le_bytes());
+    delta.extend_from_slice(&count.to_le_bytes());
+    delta.extend_from_slice(&operations);
+    Ok(delta)
+}
+
+/// Applies a delta created by [encode_binary_delta] to an encoded TDB file,
+/// and returns the encoded new file.
+///
+/// The records of the `old` file are copied byte by byte, so records that
+/// are not part of the delta are kept exactly as they were stored. This
+/// includes all records of a FLARM id that occurs more than once.
+pub fn apply_binary_delta(old: &[u8], delta: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    let layout = Layout::read(old, &DecodeOptions::default())?;
+    let mut records: BTreeMap<u32, Vec<[u8; RECORD_SIZE]>> = BTreeMap::new();
+    for i in 0..layout.record_count {
+        let data = layout.record_data(old, i);
+        records
+            .entry(record_flarm_id(data))
+            .or_default()
+            .push(*data);
+    }
+
+    let mut delta = delta;
+    let magic: [u8; 4] = take(&mut delta)?;
+    if magic != DELTA_MAGIC {
+        return Err(DecodeError::InvalidMagic(magic));
+    }
+    let version = u32::from_le_bytes(take(&mut delta)?);
+    let count = u32::from_le_bytes(take(&mut delta)?);
+
+    for _ in 0..count {
+        let [operation] = take(&mut delta)?;
+        match operation {
+            OP_UPSERT => {
+                let data: [u8; RECORD_SIZE] = take(&mut delta)?;
+                records.insert(record_flarm_id(&data), vec![data]);
+            }
+            OP_REMOVE => {
+                records.remove(&u32::from_le_bytes(take(&mut delta)?));
+            }
+            operation => return Err(DecodeError::InvalidDeltaOperation(operation)),
+        }
+    }
+
+    let record_count: usize = records.values().map(Vec::len).sum();
+    let mut data = Vec::with_capacity(
+        HEADER_SIZE + record_count * (INDEX_ENTRY_SIZE + RECORD_SIZE) + PADDING_SIZE,
+    );
+    data.extend_from_slice(&MAGIC);
+    data.extend_from_slice(&version.to_le_bytes());
+    data.extend_from_slice(&(record_count as u32).to_le_bytes());
+    for (id, duplicates) in &records {
+        for _ in duplicates {
+            data.extend_from_slice(&id.to_le_bytes());
+        }
+    }
+    data.extend_from_slice(&[0u8; PADDING_SIZE]);
+    for record in records.values().flatten() {
+        data.extend_from_slice(record);
+    }
+
+    Ok(data)
+}
+
+/// Returns the number of records that were added, removed, or changed in
+/// `new` compared to the encoded `current` file, e.g. to decide whether an
+/// update is worth deploying.
+///
+/// Records are compared as decoded, so `new` should use the same
+/// formatting the decoder produces, e.g. frequencies with three decimals.
+/// Records of the current file that fail to decode are ignored.
+pub fn change_count(current: &[u8], new: &File) -> Result<usize, DecodeError> {
+    let current = decode_file(current)?;
+    let current = File {
+        version: current.version,
+        records: current.records.into_iter().filter_map(Result::ok).collect(),
+    };
+
+    let diff = current.diff(new);
+    Ok(diff.added.len() + diff.removed.len() + diff.changed.len())
+}
+
+fn take<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], DecodeError> {
+    let (head, tail) = data
+        .split_first_chunk::<N>()
+        .ok_or(DecodeError::UnexpectedEof)?;
+    *data = tail;
+    Ok(*head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Record;
+    use crate::tdb::encode_file;
+    use insta::assert_debug_snapshot;
+
+    fn make_record(flarm_id: &str, registration: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn applying_delta_produces_new_file() {
+        let old = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "D-0001"),
+                make_record("000002", "D-0002"),
+                make_record("000003", "D-0003"),
+            ],
+        };
+        let new = File {
+            version: 2,
+            records: vec![
+                make_record("000004", "D-0004"),
+                make_record("000003", "D-0003"),
+                make_record("000002", "D-2222"),
+            ],
+        };
+
+        let delta = encode_binary_delta(&old, &new).unwrap();
+        // header + one removal + two upserts
+        assert_eq!(delta.len(), 12 + (1 + 4) + 2 * (1 + RECORD_SIZE));
+
+        let old_data = encode_file(&old).unwrap();
+        let new_data = apply_binary_delta(&old_data, &delta).unwrap();
+        assert_eq!(new_data, encode_file(&new).unwrap());
+    }
+
+    #[test]
+    fn applying_delta_keeps_duplicate_records() {
+        let old = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "D-0001"),
+                make_record("000001", "D-1111"),
+            ],
+        };
+        let mut new = old.clone();
+        new.version = 2;
+        new.records.push(make_record("000002", "D-0002"));
+
+        let delta = encode_binary_delta(&old, &new).unwrap();
+        let new_data = apply_binary_delta(&encode_file(&old).unwrap(), &delta).unwrap();
+        assert_eq!(decode_file(&new_data).unwrap().records.len(), 3);
+        assert_eq!(new_data, encode_file(&new).unwrap());
+    }
+
+    #[test]
+    fn change_count_works() {
+        let current = File {
+            version: 1,
+            records: vec![
+                make_record("000001", "D-0001"),
+                make_record("000002", "D-0002"),
+                make_record("000003", "D-0003"),
+            ],
+        };
+        let data = encode_file(&current).unwrap();
+        assert_eq!(change_count(&data, &current).unwrap(), 0);
+
+        let new = File {
+            version: 2,
+            records: vec![
+                make_record("000004", "D-0004"),
+                make_record("000003", "D-0003"),
+                make_record("000002", "D-2222"),
+            ],
+        };
+        assert_eq!(change_count(&data, &new).unwrap(), 3);
+        assert_debug_snapshot!(change_count(b"", &new).unwrap_err(), @"UnexpectedEof");
+    }
+
+    #[test]
+    fn applying_empty_delta_keeps_records() {
+        let file = File {
+            version: 1,
+            records: vec![make_record("000001", "D-0001")],
+        };
+
+        let delta = encode_binary_delta(&file, &file).unwrap();
+        assert_eq!(delta.len(), 12);
+
+        let data = encode_file(&file).unwrap();
+        assert_eq!(apply_binary_delta(&data, &delta).unwrap(), data);
+    }
+
+    #[test]
+    fn applying_delta_fails_for_invalid_input() {
+        let data = encode_file(&File {
+            version: 1,
+            records: vec![],
+        })
+        .unwrap();
+
+        assert_debug_snapshot!(apply_binary_delta(&data, b"TDB").unwrap_err(), @"UnexpectedEof");
+
+        let mut delta = b"TDBD".to_vec();
+        delta.extend_from_slice(&1u32.to_le_bytes());
+        delta.extend_from_slice(&1u32.to_le_bytes());
+        delta.push(0x07);
+        assert_debug_snapshot!(apply_binary_delta(&data, &delta).unwrap_err(), @r###"
+        InvalidDeltaOperation(
+            7,
+        )
+        "###);
+    }
+}
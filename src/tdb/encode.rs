@@ -1,51 +1,192 @@
 use super::consts::*;
-use crate::{File, Record};
-use std::io::{Cursor, Write};
+use super::options::{DuplicatePolicy, EncodeOptions, FieldPadding, SecondarySort};
+use crate::frequency::parse_khz;
+use crate::{File, FrequencyBand, Record};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Seek, SeekFrom, Write};
+use std::path::Path;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum EncodeError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
-    #[error("invalid FLARM id: {0}")]
-    InvalidFlarmId(String),
-    #[error("invalid frequency: {0}")]
-    InvalidFrequency(String),
+    /// `index` is the position of the record in [File::records], if the
+    /// value belongs to a record.
+    #[error("invalid FLARM id: {value}{}", at_record(.index))]
+    InvalidFlarmId { value: String, index: Option<usize> },
+    /// `index` is the position of the record in [File::records], if the
+    /// value belongs to a record.
+    #[error("invalid frequency: {value}{}", at_record(.index))]
+    InvalidFrequency { value: String, index: Option<usize> },
+    #[error("expected {expected} records, got {actual}")]
+    RecordCountMismatch { expected: usize, actual: usize },
+    #[error("records are not sorted by FLARM id: {0}")]
+    UnsortedRecords(String),
+    #[error("missing {field} field")]
+    MissingField { field: &'static str },
+    #[error("duplicate FLARM id: {0}")]
+    DuplicateFlarmId(String),
+    #[error("too many records: {0}")]
+    TooManyRecords(usize),
+    /// `index` is the position of the record in [File::records], if the
+    /// value belongs to a record.
+    #[error("{field} field is too long{}", at_record(.index))]
+    FieldTooLong {
+        field: &'static str,
+        index: Option<usize>,
+    },
+}
+
+impl EncodeError {
+    /// Sets the record position of errors about an invalid value.
+    pub(crate) fn with_index(self, i: usize) -> Self {
+        match self {
+            EncodeError::InvalidFlarmId { value, .. } => EncodeError::InvalidFlarmId {
+                value,
+                index: Some(i),
+            },
+            EncodeError::InvalidFrequency { value, .. } => EncodeError::InvalidFrequency {
+                value,
+                index: Some(i),
+            },
+            EncodeError::FieldTooLong { field, .. } => EncodeError::FieldTooLong {
+                field,
+                index: Some(i),
+            },
+            error => error,
+        }
+    }
+}
+
+fn at_record(index: &Option<usize>) -> String {
+    index.map_or_else(String::new, |i| format!(" (record {i})"))
 }
 
 pub fn encode_file(file: &File) -> Result<Vec<u8>, EncodeError> {
-    let mut writer = Writer::new(Cursor::new(Vec::new()));
+    encode_file_with(file, &EncodeOptions::default())
+}
+
+/// Encodes a FlarmNet file in TDB format using [EncodeOptions::strict].
+///
+/// Unlike [encode_file], this rejects records without a FLARM id or
+/// registration with [EncodeError::MissingField].
+pub fn encode_file_strict(file: &File) -> Result<Vec<u8>, EncodeError> {
+    encode_file_with(file, &EncodeOptions::strict())
+}
+
+/// Encodes a FlarmNet file in TDB format using the given [EncodeOptions].
+pub fn encode_file_with(file: &File, options: &EncodeOptions) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = Writer::with_options(Cursor::new(Vec::new()), options.clone());
     writer.write(file)?;
     Ok(writer.into_inner().into_inner())
 }
 
+/// Encodes a FlarmNet file in TDB format and writes it to the given path.
+///
+/// The file is only written if encoding succeeds.
+pub fn encode_path<P: AsRef<Path>>(file: &File, path: P) -> Result<(), EncodeError> {
+    let data = encode_file(file)?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Encodes a single record, as it is stored on disk, without the header and
+/// index of a file.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::Record;
+/// let record = Record {
+///     flarm_id: "3EE3C7".to_string(),
+///     registration: "D-0816".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let data = flarmnet::tdb::encode_record(&record).unwrap();
+/// assert_eq!(flarmnet::tdb::decode_record(&data).unwrap(), record);
+/// ```
+pub fn encode_record(record: &Record) -> Result<[u8; RECORD_SIZE], EncodeError> {
+    let writer = Writer::new(std::io::sink());
+    writer.record_data(writer.flarm_id(record)?, record)
+}
+
+#[derive(Debug, Clone)]
 pub struct Writer<W: Write> {
     writer: W,
+    options: EncodeOptions,
+    skipped: Vec<usize>,
 }
 
 impl<W: Write> Writer<W> {
     pub fn new(inner: W) -> Self {
-        Self { writer: inner }
+        Self::with_options(inner, EncodeOptions::default())
+    }
+
+    pub fn with_options(inner: W, options: EncodeOptions) -> Self {
+        Self {
+            writer: inner,
+            options,
+            skipped: Vec::new(),
+        }
     }
 
     pub fn write(&mut self, file: &File) -> Result<(), EncodeError> {
-        let mut entries: Vec<(u32, &Record)> = file
-            .records
-            .iter()
-            .map(|record| Ok((parse_flarm_id(&record.flarm_id)?, record)))
-            .collect::<Result<_, EncodeError>>()?;
+        self.write_chunked(file, usize::MAX, |_| {})
+    }
 
-        entries.sort_by_key(|(id, _)| *id);
+    /// Writes the file like [Writer::write], but writes the records in
+    /// batches of `chunk_size` and calls `on_chunk` with the total number of
+    /// records written so far after every batch.
+    ///
+    /// This can be used to report progress during large exports. A
+    /// `chunk_size` of zero is treated as one.
+    pub fn write_chunked(
+        &mut self,
+        file: &File,
+        chunk_size: usize,
+        mut on_chunk: impl FnMut(usize),
+    ) -> Result<(), EncodeError> {
+        self.skipped.clear();
 
-        let count = entries.len() as u32;
+        // FLARM ids with the positions of their records in `file.records`
+        let mut entries: Vec<(u32, usize, &Record)> = Vec::with_capacity(file.records.len());
+        for (i, record) in file.records.iter().enumerate() {
+            if let Some(id) = self.entry_id(i, record)? {
+                entries.push((id, i, record));
+            }
+        }
 
-        // header
-        self.writer.write_all(&MAGIC)?;
-        self.writer.write_all(&file.version.to_le_bytes())?;
-        self.writer.write_all(&count.to_le_bytes())?;
+        match self.options.secondary_sort {
+            _ if self.options.preserve_order => {}
+            SecondarySort::None => entries.sort_by_key(|(id, _, _)| *id),
+            SecondarySort::ByRegistration => entries.sort_by(|(a_id, _, a), (b_id, _, b)| {
+                a_id.cmp(b_id)
+                    .then_with(|| a.registration.cmp(&b.registration))
+            }),
+        }
+
+        match self.options.on_duplicate {
+            DuplicatePolicy::Keep => {}
+            DuplicatePolicy::Dedup => {
+                // later positions overwrite earlier ones
+                let last: HashMap<u32, usize> =
+                    entries.iter().map(|(id, i, _)| (*id, *i)).collect();
+                entries.retain(|(id, i, _)| last[id] == *i);
+            }
+            DuplicatePolicy::Error => {
+                let mut seen = HashSet::new();
+                if let Some((_, _, record)) = entries.iter().find(|(id, _, _)| !seen.insert(*id)) {
+                    return Err(EncodeError::DuplicateFlarmId(record.flarm_id.clone()));
+                }
+            }
+        }
+
+        self.write_header(file.version, entries.len())?;
 
         // index
-        for (id, _) in &entries {
+        for (id, _, _) in &entries {
             self.writer.write_all(&id.to_le_bytes())?;
         }
 
@@ -53,28 +194,123 @@ impl<W: Write> Writer<W> {
         self.writer.write_all(&[0u8; PADDING_SIZE])?;
 
         // records
-        for (id, record) in &entries {
-            self.write_record(*id, record)?;
+        let mut written = 0;
+        for chunk in entries.chunks(chunk_size.max(1)) {
+            for (id, i, record) in chunk {
+                self.write_record(*id, record)
+                    .map_err(|error| error.with_index(*i))?;
+            }
+            written += chunk.len();
+            on_chunk(written);
         }
 
         Ok(())
     }
 
+    /// Returns the positions of the records that were skipped by the last
+    /// write because of an invalid FLARM id, if
+    /// [EncodeOptions::skip_invalid] is set.
+    pub fn skipped(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    /// Returns the FLARM id to store for the record at position `i`, or
+    /// `None` if the record is skipped.
+    fn entry_id(&mut self, i: usize, record: &Record) -> Result<Option<u32>, EncodeError> {
+        match self.flarm_id(record).map_err(|error| error.with_index(i)) {
+            Ok(id) => Ok(Some(id)),
+            Err(EncodeError::InvalidFlarmId { .. }) if self.options.skip_invalid => {
+                self.skipped.push(i);
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn write_header(&mut self, version: u32, count: usize) -> Result<(), EncodeError> {
+        if self.options.max_records.is_some_and(|max| count > max) {
+            return Err(EncodeError::TooManyRecords(count));
+        }
+
+        self.writer.write_all(&MAGIC)?;
+        self.writer.write_all(&version.to_le_bytes())?;
+        self.writer.write_all(&(count as u32).to_le_bytes())?;
+        Ok(())
+    }
+
+    pub(super) fn flarm_id(&self, record: &Record) -> Result<u32, EncodeError> {
+        if self.options.require_ids {
+            if record.flarm_id.trim().is_empty() {
+                return Err(EncodeError::MissingField { field: "flarm_id" });
+            }
+            if record.registration.trim().is_empty() {
+                return Err(EncodeError::MissingField {
+                    field: "registration",
+                });
+            }
+        }
+
+        let invalid = || EncodeError::InvalidFlarmId {
+            value: record.flarm_id.clone(),
+            index: None,
+        };
+        let id = if self.options.mask_flarm_id {
+            let id = u32::from_str_radix(&record.flarm_id, 16).map_err(|_| invalid())?;
+            (id & FLARM_ID_MASK) | (u32::from(record.address_type) << 24)
+        } else if record.address_type != 0 {
+            // the address type can only be stored in the masked high bits
+            return Err(invalid());
+        } else {
+            parse_flarm_id(&record.flarm_id)?
+        };
+        if self.options.strict_flarm_id && id & FLARM_ID_MASK == 0 {
+            return Err(invalid());
+        }
+        Ok(id)
+    }
+
     fn write_record(&mut self, flarm_id: u32, record: &Record) -> Result<(), EncodeError> {
-        let frequency = parse_frequency(&record.frequency)?;
+        let buf = self.record_data(flarm_id, record)?;
+        self.writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Encodes a single record, as it is stored on disk.
+    pub(super) fn record_data(
+        &self,
+        flarm_id: u32,
+        record: &Record,
+    ) -> Result<[u8; RECORD_SIZE], EncodeError> {
+        let frequency = parse_frequency(&record.frequency, self.options.raw_frequency)?;
+        if self.options.require_airband
+            && frequency.is_some_and(|khz| FrequencyBand::from_khz(khz) == FrequencyBand::Other)
+        {
+            return Err(EncodeError::InvalidFrequency {
+                value: record.frequency.clone(),
+                index: None,
+            });
+        }
+        let frequency = frequency.unwrap_or(self.options.empty_frequency.stored_value());
 
         let mut buf = [0u8; RECORD_SIZE];
         buf[FLARM_ID_OFFSET..FLARM_ID_OFFSET + 4].copy_from_slice(&flarm_id.to_le_bytes());
         buf[FREQUENCY_OFFSET..FREQUENCY_OFFSET + 4].copy_from_slice(&frequency.to_le_bytes());
-        // reserved at offset 8..16 stays zero
-        write_string(&mut buf, CALL_SIGN_OFFSET, &record.call_sign);
-        write_string(&mut buf, PILOT_NAME_OFFSET, &record.pilot_name);
-        write_string(&mut buf, AIRFIELD_OFFSET, &record.airfield);
-        write_string(&mut buf, PLANE_TYPE_OFFSET, &record.plane_type);
-        write_string(&mut buf, REGISTRATION_OFFSET, &record.registration);
+        buf[RESERVED_OFFSET..RESERVED_OFFSET + RESERVED_SIZE].copy_from_slice(&record.reserved);
+        let fields = [
+            (CALL_SIGN_OFFSET, "call_sign", &record.call_sign),
+            (PILOT_NAME_OFFSET, "pilot_name", &record.pilot_name),
+            (AIRFIELD_OFFSET, "airfield", &record.airfield),
+            (PLANE_TYPE_OFFSET, "plane_type", &record.plane_type),
+            (REGISTRATION_OFFSET, "registration", &record.registration),
+        ];
+        for (offset, field, value) in fields {
+            if self.options.error_on_truncation && value.len() > STRING_FIELD_SIZE - 1 {
+                return Err(EncodeError::FieldTooLong { field, index: None });
+            }
+            write_string(&mut buf, offset, value, &self.options);
+        }
 
-        self.writer.write_all(&buf)?;
-        Ok(())
+        Ok(buf)
     }
 
     pub fn into_inner(self) -> W {
@@ -82,39 +318,175 @@ impl<W: Write> Writer<W> {
     }
 }
 
-fn write_string(buf: &mut [u8; RECORD_SIZE], offset: usize, value: &str) {
+impl<W: Write + Seek> Writer<W> {
+    /// Writes `count` records that are already sorted by FLARM id, without
+    /// buffering the records themselves.
+    ///
+    /// Since the index precedes the record data on disk, the space for the
+    /// index is reserved first and filled in by seeking back once all records
+    /// have been written. Only the FLARM ids are kept in memory in the
+    /// meantime.
+    ///
+    /// Fails with [EncodeError::UnsortedRecords] or
+    /// [EncodeError::RecordCountMismatch] if the records are not sorted or
+    /// their number differs from `count`, and with
+    /// [EncodeError::DuplicateFlarmId] for [DuplicatePolicy::Error]. The
+    /// output is incomplete in that case. Records skipped because of
+    /// [EncodeOptions::skip_invalid] are not included in `count`.
+    pub fn write_sorted<I>(
+        &mut self,
+        version: u32,
+        count: usize,
+        records: I,
+    ) -> Result<(), EncodeError>
+    where
+        I: IntoIterator<Item = Record>,
+    {
+        self.write_header(version, count)?;
+
+        let index_position = self.writer.stream_position()?;
+        self.writer
+            .write_all(&vec![0u8; count * INDEX_ENTRY_SIZE])?;
+        self.writer.write_all(&[0u8; PADDING_SIZE])?;
+
+        self.skipped.clear();
+
+        let mut ids = Vec::with_capacity(count);
+        for (i, record) in records.into_iter().enumerate() {
+            let Some(id) = self.entry_id(i, &record)? else {
+                continue;
+            };
+            if ids.last().is_some_and(|last| *last > id) {
+                return Err(EncodeError::UnsortedRecords(record.flarm_id));
+            }
+            if self.options.on_duplicate == DuplicatePolicy::Error && ids.last() == Some(&id) {
+                return Err(EncodeError::DuplicateFlarmId(record.flarm_id));
+            }
+            if ids.len() == count {
+                return Err(EncodeError::RecordCountMismatch {
+                    expected: count,
+                    actual: count + 1,
+                });
+            }
+
+            self.write_record(id, &record)
+                .map_err(|error| error.with_index(i))?;
+            ids.push(id);
+        }
+
+        if ids.len() != count {
+            return Err(EncodeError::RecordCountMismatch {
+                expected: count,
+                actual: ids.len(),
+            });
+        }
+
+        let end_position = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(index_position))?;
+        for id in &ids {
+            self.writer.write_all(&id.to_le_bytes())?;
+        }
+        self.writer.seek(SeekFrom::Start(end_position))?;
+
+        Ok(())
+    }
+}
+
+/// Returns the size in bytes of the file encoded by [encode_file], without
+/// encoding it.
+///
+/// Options that drop records, like [EncodeOptions::skip_invalid] or
+/// [DuplicatePolicy::Dedup], result in smaller files.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File { version: 1, records: vec![Record::default(); 3] };
+///
+/// assert_eq!(flarmnet::tdb::encoded_size(&file), 12 + 3 * 4 + 8 + 3 * 96);
+/// ```
+pub fn encoded_size(file: &File) -> usize {
+    let count = file.records.len();
+    HEADER_SIZE + count * INDEX_ENTRY_SIZE + PADDING_SIZE + count * RECORD_SIZE
+}
+
+/// Returns the maximum number of UTF-8 bytes that can be stored in the given
+/// field without truncation.
+///
+/// Returns `None` for the numeric `flarm_id` and `frequency` fields, and for
+/// unknown fields.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(flarmnet::tdb::field_max_bytes("pilot_name"), Some(15));
+/// assert_eq!(flarmnet::tdb::field_max_bytes("frequency"), None);
+/// ```
+pub fn field_max_bytes(field: &str) -> Option<usize> {
+    match field {
+        "pilot_name" | "airfield" | "plane_type" | "registration" | "call_sign" => {
+            Some(STRING_FIELD_SIZE - 1)
+        }
+        _ => None,
+    }
+}
+
+fn write_string(buf: &mut [u8; RECORD_SIZE], offset: usize, value: &str, options: &EncodeOptions) {
+    let field = &mut buf[offset..offset + STRING_FIELD_SIZE];
     let max_content = STRING_FIELD_SIZE - 1;
-    let truncated = if value.len() > max_content {
-        &value[..value.floor_char_boundary(max_content)]
+
+    let len = if value.len() <= max_content {
+        field[..value.len()].copy_from_slice(value.as_bytes());
+        value.len()
     } else {
-        value
+        let marker = options.truncation_marker;
+        let marker_len = marker.map_or(0, char::len_utf8);
+        let truncated = &value[..value.floor_char_boundary(max_content - marker_len)];
+        field[..truncated.len()].copy_from_slice(truncated.as_bytes());
+
+        if let Some(marker) = marker {
+            marker.encode_utf8(&mut field[truncated.len()..truncated.len() + marker_len]);
+        }
+        truncated.len() + marker_len
     };
-    buf[offset..offset + truncated.len()].copy_from_slice(truncated.as_bytes());
+
     // remaining bytes are already zero from initialization
+    if options.field_padding == FieldPadding::Space {
+        field[len..].fill(b' ');
+    }
 }
 
-fn parse_flarm_id(s: &str) -> Result<u32, EncodeError> {
-    let id = u32::from_str_radix(s, 16).map_err(|_| EncodeError::InvalidFlarmId(s.to_string()))?;
-    if id > 0xFFFFFF {
-        return Err(EncodeError::InvalidFlarmId(s.to_string()));
+pub(crate) fn parse_flarm_id(s: &str) -> Result<u32, EncodeError> {
+    let invalid = || EncodeError::InvalidFlarmId {
+        value: s.to_string(),
+        index: None,
+    };
+    let id = u32::from_str_radix(s, 16).map_err(|_| invalid())?;
+    if id > FLARM_ID_MASK {
+        return Err(invalid());
     }
     Ok(id)
 }
 
-fn parse_frequency(s: &str) -> Result<u32, EncodeError> {
+/// Parses a frequency like [parse_khz], or returns `None` if it is empty.
+pub(crate) fn parse_frequency(s: &str, raw: bool) -> Result<Option<u32>, EncodeError> {
     if s.is_empty() {
-        return Ok(0);
+        return Ok(None);
     }
-    let mhz: f64 = s
-        .parse()
-        .map_err(|_| EncodeError::InvalidFrequency(s.to_string()))?;
-    Ok((mhz * 1000.0).round() as u32)
+
+    parse_khz(s, raw)
+        .map(Some)
+        .ok_or_else(|| EncodeError::InvalidFrequency {
+            value: s.to_string(),
+            index: None,
+        })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tdb::decode_file;
+    use crate::tdb::{DecodeOptions, EmptyFrequency, decode_file, decode_file_with, decode_path};
     use insta::assert_debug_snapshot;
 
     fn make_file(records: Vec<Record>) -> File {
@@ -141,6 +513,96 @@ mod tests {
             airfield: airfield.to_string(),
             plane_type: plane_type.to_string(),
             registration: registration.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Deterministic xorshift generator for the randomized tests.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+
+        fn string(&mut self) -> String {
+            const CHARS: [char; 10] = ['A', 'z', '0', ' ', '-', '.', 'ä', 'Ø', '山', '🛩'];
+            let len = self.below(24);
+            (0..len)
+                .map(|_| CHARS[self.below(CHARS.len() as u64) as usize])
+                .collect()
+        }
+
+        fn record(&mut self) -> Record {
+            // small ids make duplicates likely
+            let flarm_id = match self.below(2) {
+                0 => self.below(0x10),
+                _ => self.below(u64::from(FLARM_ID_MASK) + 1),
+            };
+            let frequency = match self.below(4) {
+                0 => String::new(),
+                _ => {
+                    let khz = self.below(1_000_000);
+                    format!("{}.{:03}", khz / 1000, khz % 1000)
+                }
+            };
+
+            Record {
+                flarm_id: format!("{flarm_id:06X}"),
+                pilot_name: self.string(),
+                airfield: self.string(),
+                plane_type: self.string(),
+                registration: self.string(),
+                call_sign: self.string(),
+                frequency,
+                reserved: self.next().to_le_bytes(),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn encoding_round_trips_random_files() {
+        let truncate = |value: &mut String| {
+            value.truncate(value.floor_char_boundary(STRING_FIELD_SIZE - 1));
+        };
+
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..500 {
+            let count = rng.below(40);
+            let file = File {
+                version: rng.next() as u32,
+                records: (0..count).map(|_| rng.record()).collect(),
+            };
+
+            // the encoder sorts by FLARM id, keeps duplicates in input order,
+            // truncates text fields and decodes `0.000` as empty
+            let mut expected = file.records.clone();
+            expected.sort_by_key(|record| parse_flarm_id(&record.flarm_id).unwrap());
+            for record in &mut expected {
+                truncate(&mut record.pilot_name);
+                truncate(&mut record.airfield);
+                truncate(&mut record.plane_type);
+                truncate(&mut record.registration);
+                truncate(&mut record.call_sign);
+                if record.frequency == "0.000" {
+                    record.frequency.clear();
+                }
+            }
+
+            let encoded = encode_file(&file).unwrap();
+            assert_eq!(encoded.len(), encoded_size(&file));
+            let decoded = decode_file(&encoded).unwrap();
+            assert_eq!(decoded.version, file.version);
+            let records: Vec<_> = decoded.records.into_iter().map(Result::unwrap).collect();
+            assert_eq!(records, expected, "{file:?}");
         }
     }
 
@@ -152,15 +614,29 @@ mod tests {
         let encoded = encode_file(&file).unwrap();
         let decoded = decode_file(&encoded).unwrap();
         assert_eq!(decoded.version, 1);
-        assert_eq!(decoded.records.len(), 1);
-        let record = decoded.records[0].as_ref().unwrap();
-        assert_eq!(record.flarm_id, "3EE3C7");
-        assert_eq!(record.frequency, "123.500");
-        assert_eq!(record.call_sign, "SG");
-        assert_eq!(record.pilot_name, "John Doe");
-        assert_eq!(record.airfield, "EDKA");
-        assert_eq!(record.plane_type, "LS6a");
-        assert_eq!(record.registration, "D-0816");
+        assert_eq!(decoded.records, vec![Ok(file.records[0].clone())]);
+    }
+
+    #[test]
+    fn encoding_to_path_round_trips() {
+        let dir = std::env::temp_dir().join(format!("flarmnet-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("flarmnet.tdb");
+
+        let file = make_file(vec![make_record(
+            "3EE3C7", "123.500", "SG", "", "", "", "D-0816",
+        )]);
+        encode_path(&file, &path).unwrap();
+
+        let decoded = decode_path(&path).unwrap();
+        assert_eq!(decoded.records, vec![Ok(file.records[0].clone())]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_debug_snapshot!(decode_path(&path).unwrap_err(), @r###"
+        Io(
+            "entity not found",
+        )
+        "###);
     }
 
     #[test]
@@ -176,8 +652,36 @@ mod tests {
         )]);
         let encoded = encode_file(&file).unwrap();
         let decoded = decode_file(&encoded).unwrap();
-        let record = decoded.records[0].as_ref().unwrap();
-        assert_eq!(record.frequency, "");
+        assert_eq!(decoded.records, vec![Ok(file.records[0].clone())]);
+    }
+
+    #[test]
+    fn encoding_stores_empty_frequency_as_sentinel() {
+        let file = make_file(vec![
+            make_record("000001", "", "", "", "", "Paraglider", ""),
+            make_record("000002", "0", "", "", "", "", ""),
+        ]);
+        let empty_frequency = EmptyFrequency::Sentinel(0xFFFFFFFF);
+        let options = EncodeOptions {
+            empty_frequency,
+            ..Default::default()
+        };
+        let encoded = encode_file_with(&file, &options).unwrap();
+
+        let offset = HEADER_SIZE + 2 * INDEX_ENTRY_SIZE + PADDING_SIZE + FREQUENCY_OFFSET;
+        assert_eq!(encoded[offset..offset + 4], [0xFF; 4]);
+
+        let options = DecodeOptions {
+            empty_frequency,
+            ..Default::default()
+        };
+        let decoded = decode_file_with(&encoded, &options).unwrap();
+        let frequencies: Vec<&str> = decoded
+            .records
+            .iter()
+            .map(|r| r.as_ref().unwrap().frequency.as_str())
+            .collect();
+        assert_eq!(frequencies, vec!["", "0.000"]);
     }
 
     #[test]
@@ -197,6 +701,107 @@ mod tests {
         assert_eq!(ids, vec!["000000", "000001", "00000F"]);
     }
 
+    #[test]
+    fn encoding_sorts_duplicates_by_registration() {
+        let file = make_file(vec![
+            make_record("000002", "", "", "", "", "", "D-0002"),
+            make_record("000001", "", "", "", "", "", "D-2222"),
+            make_record("000001", "", "", "", "", "", "D-1111"),
+        ]);
+        let registrations = |options: &EncodeOptions| {
+            let encoded = encode_file_with(&file, options).unwrap();
+            decode_file(&encoded)
+                .unwrap()
+                .records
+                .into_iter()
+                .map(|record| record.unwrap().registration)
+                .collect::<Vec<_>>()
+        };
+
+        let options = EncodeOptions::default();
+        assert_eq!(registrations(&options), ["D-2222", "D-1111", "D-0002"]);
+
+        let options = EncodeOptions::builder()
+            .secondary_sort(SecondarySort::ByRegistration)
+            .build();
+        assert_eq!(registrations(&options), ["D-1111", "D-2222", "D-0002"]);
+    }
+
+    #[test]
+    fn encoding_preserves_order() {
+        let file = make_file(vec![
+            make_record("00000F", "", "", "", "", "", "D-000F"),
+            make_record("000001", "", "", "", "", "", "D-0001"),
+            make_record("000002", "", "", "", "", "", "D-0002"),
+            make_record("000001", "", "", "", "", "", "D-1111"),
+        ]);
+        let options = EncodeOptions::builder().preserve_order(true).build();
+
+        let decoded = decode_file(&encode_file_with(&file, &options).unwrap()).unwrap();
+        assert_eq!(decoded.index, [0x0F, 0x01, 0x02, 0x01]);
+        let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+        assert_eq!(records.unwrap(), file.records);
+
+        let options = EncodeOptions::builder()
+            .preserve_order(true)
+            .on_duplicate(DuplicatePolicy::Dedup)
+            .build();
+        let decoded = decode_file(&encode_file_with(&file, &options).unwrap()).unwrap();
+        assert_eq!(decoded.index, [0x0F, 0x02, 0x01]);
+    }
+
+    #[test]
+    fn encoding_handles_duplicates_by_policy() {
+        let file = make_file(vec![
+            make_record("000002", "", "", "", "", "", "D-0002"),
+            make_record("000001", "", "", "", "", "", "D-1111"),
+            make_record("000001", "", "", "", "", "", "D-2222"),
+        ]);
+        let encode = |on_duplicate| {
+            let options = EncodeOptions::builder().on_duplicate(on_duplicate).build();
+            encode_file_with(&file, &options)
+        };
+        let registrations = |encoded: Vec<u8>| {
+            decode_file(&encoded)
+                .unwrap()
+                .records
+                .into_iter()
+                .map(|record| record.unwrap().registration)
+                .collect::<Vec<_>>()
+        };
+
+        let encoded = encode(DuplicatePolicy::Keep).unwrap();
+        assert_eq!(registrations(encoded), ["D-1111", "D-2222", "D-0002"]);
+
+        let encoded = encode(DuplicatePolicy::Dedup).unwrap();
+        assert_eq!(registrations(encoded), ["D-2222", "D-0002"]);
+
+        assert_debug_snapshot!(encode(DuplicatePolicy::Error).unwrap_err(), @r###"
+        DuplicateFlarmId(
+            "000001",
+        )
+        "###);
+    }
+
+    #[test]
+    fn writing_sorted_records_fails_for_duplicates() {
+        let options = EncodeOptions::builder()
+            .on_duplicate(DuplicatePolicy::Error)
+            .build();
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), options);
+        let records = vec![
+            make_record("000001", "", "", "", "", "", ""),
+            make_record("000001", "", "", "", "", "", ""),
+        ];
+
+        let result = writer.write_sorted(1, 2, records);
+        assert_debug_snapshot!(result.unwrap_err(), @r###"
+        DuplicateFlarmId(
+            "000001",
+        )
+        "###);
+    }
+
     #[test]
     fn encoding_truncates_long_strings() {
         let file = make_file(vec![make_record(
@@ -232,15 +837,257 @@ mod tests {
         assert_eq!(record.call_sign, "01234567890123");
     }
 
+    #[test]
+    fn encoding_appends_truncation_marker() {
+        let options = EncodeOptions {
+            truncation_marker: Some('~'),
+            ..Default::default()
+        };
+        let file = make_file(vec![make_record(
+            "000001",
+            "",
+            "0123456789ABCDEF",
+            "01234567890123Ä",
+            "EDKA",
+            "",
+            "",
+        )]);
+        let encoded = encode_file_with(&file, &options).unwrap();
+        let decoded = decode_file(&encoded).unwrap();
+        let record = decoded.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "0123456789ABCD~");
+        assert_eq!(record.pilot_name, "01234567890123~");
+        assert_eq!(record.airfield, "EDKA");
+    }
+
+    #[test]
+    fn encoding_fits_multibyte_truncation_marker() {
+        let options = EncodeOptions {
+            truncation_marker: Some('…'),
+            ..Default::default()
+        };
+        let file = make_file(vec![make_record(
+            "000001",
+            "",
+            "0123456789ABCDEF",
+            "",
+            "",
+            "",
+            "",
+        )]);
+        let encoded = encode_file_with(&file, &options).unwrap();
+        let decoded = decode_file(&encoded).unwrap();
+        let record = decoded.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "0123456789AB…");
+    }
+
+    #[test]
+    fn encoding_pads_fields_with_spaces() {
+        let file = make_file(vec![make_record(
+            "000001",
+            "",
+            "SG",
+            "0123456789ABCDEF",
+            "",
+            "",
+            "D-0816",
+        )]);
+        let options = EncodeOptions {
+            field_padding: FieldPadding::Space,
+            ..Default::default()
+        };
+        let encoded = encode_file_with(&file, &options).unwrap();
+
+        let record = &encoded[encoded.len() - RECORD_SIZE..];
+        assert_eq!(
+            &record[CALL_SIGN_OFFSET..PILOT_NAME_OFFSET],
+            b"SG              "
+        );
+        assert_eq!(
+            &record[PILOT_NAME_OFFSET..AIRFIELD_OFFSET],
+            b"0123456789ABCDE "
+        );
+        assert_eq!(&record[AIRFIELD_OFFSET..PLANE_TYPE_OFFSET], &[b' '; 16]);
+
+        let options = DecodeOptions {
+            field_padding: FieldPadding::Space,
+            ..Default::default()
+        };
+        let decoded = decode_file_with(&encoded, &options).unwrap();
+        let record = decoded.records[0].as_ref().unwrap();
+        assert_eq!(record.call_sign, "SG");
+        assert_eq!(record.pilot_name, "0123456789ABCDE");
+        assert_eq!(record.airfield, "");
+        assert_eq!(record.registration, "D-0816");
+    }
+
+    #[test]
+    fn encoding_round_trips_raw_frequency() {
+        let file = make_file(vec![make_record("000001", "123500", "", "", "", "", "")]);
+        let options = EncodeOptions {
+            raw_frequency: true,
+            ..Default::default()
+        };
+        let encoded = encode_file_with(&file, &options).unwrap();
+
+        let decoded = decode_file(&encoded).unwrap();
+        assert_eq!(decoded.records[0].as_ref().unwrap().frequency, "123.500");
+
+        let decode_options = DecodeOptions {
+            raw_frequency: true,
+            ..Default::default()
+        };
+        let decoded = decode_file_with(&encoded, &decode_options).unwrap();
+        assert_eq!(decoded.records[0].as_ref().unwrap().frequency, "123500");
+
+        let file = make_file(vec![make_record("000001", "123.500", "", "", "", "", "")]);
+        assert_debug_snapshot!(encode_file_with(&file, &options).unwrap_err(), @r###"
+        InvalidFrequency {
+            value: "123.500",
+            index: Some(
+                0,
+            ),
+        }
+        "###);
+    }
+
+    #[test]
+    fn encoding_skips_invalid_flarm_ids() {
+        let file = make_file(vec![
+            make_record("3EE3C7", "", "", "", "", "", "D-0816"),
+            make_record("XYZ", "", "", "", "", "", "D-2188"),
+            make_record("1000000", "", "", "", "", "", "D-9527"),
+        ]);
+        assert!(encode_file(&file).is_err());
+
+        let options = EncodeOptions {
+            skip_invalid: true,
+            ..Default::default()
+        };
+        let mut writer = Writer::with_options(Vec::new(), options);
+        writer.write(&file).unwrap();
+        assert_eq!(writer.skipped(), [1, 2]);
+
+        let decoded = decode_file(&writer.into_inner()).unwrap();
+        assert_eq!(decoded.records.len(), 1);
+        assert_eq!(decoded.records[0].as_ref().unwrap().registration, "D-0816");
+    }
+
+    #[test]
+    fn encoding_strict_requires_ids() {
+        let valid = make_record("3EE3C7", "", "", "", "", "", "D-0816");
+        assert!(encode_file_strict(&make_file(vec![valid.clone()])).is_ok());
+
+        let file = make_file(vec![
+            valid.clone(),
+            make_record("", "", "", "", "", "", "D-2188"),
+        ]);
+        assert_debug_snapshot!(encode_file_strict(&file).unwrap_err(), @r###"
+        MissingField {
+            field: "flarm_id",
+        }
+        "###);
+
+        let file = make_file(vec![make_record("000001", "", "", "", "", "", " "), valid]);
+        assert_debug_snapshot!(encode_file_strict(&file).unwrap_err(), @r###"
+        MissingField {
+            field: "registration",
+        }
+        "###);
+        assert!(encode_file(&file).is_ok());
+    }
+
+    #[test]
+    fn writing_chunked_reports_progress() {
+        let file = make_file(
+            (0..5)
+                .map(|i| make_record(&format!("{i:06X}"), "", "", "", "", "", ""))
+                .collect(),
+        );
+
+        let mut progress = Vec::new();
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_chunked(&file, 2, |written| progress.push(written))
+            .unwrap();
+
+        assert_eq!(progress, [2, 4, 5]);
+        assert_eq!(writer.into_inner(), encode_file(&file).unwrap());
+    }
+
+    #[test]
+    fn writing_sorted_records_matches_encode_file() {
+        let file = make_file(vec![
+            make_record("000000", "123.150", "", "", "D-2188", "ASK-13", "D-2188"),
+            make_record("000001", "", "", "", "", "Paraglider", ""),
+            make_record("00000F", "", "X27", "", "D-9527", "ASW 27", "D-9527"),
+        ]);
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer
+            .write_sorted(file.version, file.records.len(), file.records.clone())
+            .unwrap();
+        let encoded = writer.into_inner().into_inner();
+
+        assert_eq!(encoded, encode_file(&file).unwrap());
+    }
+
+    #[test]
+    fn writing_sorted_records_fails_for_unsorted_records() {
+        let records = vec![
+            make_record("00000F", "", "", "", "", "", ""),
+            make_record("000001", "", "", "", "", "", ""),
+        ];
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        assert_debug_snapshot!(
+            writer.write_sorted(1, 2, records).unwrap_err(),
+            @r###"
+        UnsortedRecords(
+            "000001",
+        )
+        "###
+        );
+    }
+
+    #[test]
+    fn writing_sorted_records_fails_for_wrong_count() {
+        let records = vec![make_record("000001", "", "", "", "", "", "")];
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        assert_debug_snapshot!(
+            writer.write_sorted(1, 2, records.clone()).unwrap_err(),
+            @r###"
+        RecordCountMismatch {
+            expected: 2,
+            actual: 1,
+        }
+        "###
+        );
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        assert_debug_snapshot!(
+            writer.write_sorted(1, 0, records).unwrap_err(),
+            @r###"
+        RecordCountMismatch {
+            expected: 0,
+            actual: 1,
+        }
+        "###
+        );
+    }
+
     #[test]
     fn encoding_fails_for_invalid_flarm_id() {
         let file = make_file(vec![make_record("ZZZZZZ", "", "", "", "", "", "")]);
         assert_debug_snapshot!(
             encode_file(&file).unwrap_err(),
             @r###"
-        InvalidFlarmId(
-            "ZZZZZZ",
-        )
+        InvalidFlarmId {
+            value: "ZZZZZZ",
+            index: Some(
+                0,
+            ),
+        }
         "###
         );
     }
@@ -251,23 +1098,275 @@ mod tests {
         assert_debug_snapshot!(
             encode_file(&file).unwrap_err(),
             @r###"
-        InvalidFlarmId(
-            "1000000",
-        )
+        InvalidFlarmId {
+            value: "1000000",
+            index: Some(
+                0,
+            ),
+        }
         "###
         );
     }
 
+    #[test]
+    fn encoding_masks_flarm_id_and_keeps_address_type() {
+        let mut record = make_record("1DD1234", "", "", "", "", "", "");
+        record.address_type = 2;
+        let file = make_file(vec![record]);
+        let options = EncodeOptions {
+            mask_flarm_id: true,
+            ..Default::default()
+        };
+        let encoded = encode_file_with(&file, &options).unwrap();
+
+        let offset = HEADER_SIZE + INDEX_ENTRY_SIZE + PADDING_SIZE;
+        assert_eq!(encoded[offset..offset + 4], 0x02DD1234u32.to_le_bytes());
+
+        let options = DecodeOptions {
+            mask_flarm_id: true,
+            ..Default::default()
+        };
+        let decoded = decode_file_with(&encoded, &options).unwrap();
+        let record = decoded.records[0].as_ref().unwrap();
+        assert_eq!(
+            record,
+            &Record {
+                flarm_id: "DD1234".to_string(),
+                address_type: 2,
+                ..Default::default()
+            }
+        );
+
+        let file = make_file(vec![record.clone()]);
+        assert_debug_snapshot!(encode_file(&file).unwrap_err(), @r###"
+        InvalidFlarmId {
+            value: "DD1234",
+            index: Some(
+                0,
+            ),
+        }
+        "###);
+    }
+
     #[test]
     fn encoding_fails_for_invalid_frequency() {
         let file = make_file(vec![make_record("000001", "abc", "", "", "", "", "")]);
         assert_debug_snapshot!(
             encode_file(&file).unwrap_err(),
             @r###"
-        InvalidFrequency(
-            "abc",
-        )
+        InvalidFrequency {
+            value: "abc",
+            index: Some(
+                0,
+            ),
+        }
         "###
         );
     }
+
+    #[test]
+    fn encoding_errors_report_record_position() {
+        let file = make_file(vec![
+            make_record("000002", "", "", "", "", "", ""),
+            make_record("000001", "", "", "", "", "", ""),
+            make_record("000000", "123.4567", "", "", "", "", ""),
+        ]);
+        let error = encode_file(&file).unwrap_err();
+        assert_eq!(error.to_string(), "invalid frequency: 123.4567 (record 2)");
+
+        let file = make_file(vec![
+            make_record("000002", "", "", "", "", "", ""),
+            make_record("XYZ", "", "", "", "", "", ""),
+        ]);
+        assert_debug_snapshot!(encode_file(&file).unwrap_err(), @r###"
+        InvalidFlarmId {
+            value: "XYZ",
+            index: Some(
+                1,
+            ),
+        }
+        "###);
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let records = vec![
+            make_record("000001", "", "", "", "", "", ""),
+            make_record("000002", "abc", "", "", "", "", ""),
+        ];
+        let error = writer.write_sorted(1, 2, records).unwrap_err();
+        assert_eq!(error.to_string(), "invalid frequency: abc (record 1)");
+    }
+
+    #[test]
+    fn parsing_frequency_works() {
+        assert_eq!(parse_frequency("123.5", false).unwrap(), Some(123500));
+        assert_eq!(parse_frequency("123.500", false).unwrap(), Some(123500));
+        assert_eq!(parse_frequency("123.985", false).unwrap(), Some(123985));
+        assert_eq!(parse_frequency("123", false).unwrap(), Some(123000));
+        assert_eq!(parse_frequency("", false).unwrap(), None);
+
+        for invalid in [
+            "123.4567", "-123.5", "12a.5", ".5", "1.2.3", " 123.5", "4294968",
+        ] {
+            assert!(parse_frequency(invalid, false).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn encoding_strict_requires_airband_frequencies() {
+        let encode = |frequency: &str| {
+            let record = make_record("3EE3C7", frequency, "", "", "", "", "D-0816");
+            encode_file_strict(&make_file(vec![record]))
+        };
+
+        for valid in ["118.000", "121.5", "136.975", ""] {
+            assert!(encode(valid).is_ok(), "{valid}");
+        }
+        for invalid in [
+            "-1.0",
+            "0",
+            "117.995",
+            "136.980",
+            "137.000",
+            "5000000",
+            "4294967.295",
+        ] {
+            assert!(encode(invalid).is_err(), "{invalid}");
+        }
+        assert_debug_snapshot!(encode("137.000").unwrap_err(), @r###"
+        InvalidFrequency {
+            value: "137.000",
+            index: Some(
+                0,
+            ),
+        }
+        "###);
+
+        // out-of-band frequencies are accepted by default
+        let record = make_record("3EE3C7", "500", "", "", "", "", "D-0816");
+        let encoded = encode_file(&make_file(vec![record])).unwrap();
+        let decoded = decode_file(&encoded).unwrap();
+        assert_eq!(decoded.records[0].as_ref().unwrap().frequency, "500.000");
+    }
+
+    #[test]
+    fn encoding_strict_flarm_id_rejects_zero() {
+        let file = make_file(vec![make_record("000000", "", "", "", "", "", "D-0816")]);
+        assert!(encode_file(&file).is_ok());
+
+        let options = EncodeOptions::builder().strict_flarm_id(true).build();
+        assert_debug_snapshot!(encode_file_with(&file, &options).unwrap_err(), @r###"
+        InvalidFlarmId {
+            value: "000000",
+            index: Some(
+                0,
+            ),
+        }
+        "###);
+        assert!(encode_file_strict(&file).is_err());
+
+        let file = make_file(vec![make_record("000001", "", "", "", "", "", "D-0816")]);
+        assert!(encode_file_with(&file, &options).is_ok());
+    }
+
+    #[test]
+    fn encoding_fails_for_too_many_records() {
+        let records = (0..3)
+            .map(|i| make_record(&format!("{i:06X}"), "", "", "", "", "", "D-0816"))
+            .collect();
+        let file = make_file(records);
+
+        let options = EncodeOptions::builder().max_records(3).build();
+        assert!(encode_file_with(&file, &options).is_ok());
+
+        let options = EncodeOptions::builder().max_records(2).build();
+        assert_debug_snapshot!(encode_file_with(&file, &options).unwrap_err(), @r###"
+        TooManyRecords(
+            3,
+        )
+        "###);
+
+        let mut writer = Writer::with_options(Cursor::new(Vec::new()), options);
+        let result = writer.write_sorted(1, 3, file.records.clone());
+        assert_debug_snapshot!(result.unwrap_err(), @r###"
+        TooManyRecords(
+            3,
+        )
+        "###);
+    }
+
+    #[test]
+    fn encoding_fails_for_truncated_fields() {
+        // 14 bytes, and 16 bytes with the multibyte character
+        let file = make_file(vec![
+            make_record("000001", "", "", "Tobias Bieniek", "", "", "D-0816"),
+            make_record("000002", "", "", "", "Flugplatz Düren", "", "D-2188"),
+        ]);
+        assert!(encode_file(&file).is_ok());
+
+        let options = EncodeOptions::builder().error_on_truncation(true).build();
+        let error = encode_file_with(&file, &options).unwrap_err();
+        assert_eq!(error.to_string(), "airfield field is too long (record 1)");
+        assert_debug_snapshot!(error, @r###"
+        FieldTooLong {
+            field: "airfield",
+            index: Some(
+                1,
+            ),
+        }
+        "###);
+    }
+
+    #[test]
+    fn encoded_size_matches_encoding() {
+        for count in [0, 1, 2, 100] {
+            let records = (0..count)
+                .map(|i| make_record(&format!("{i:06X}"), "123.5", "SG", "", "", "", "D-0816"))
+                .collect();
+            let file = make_file(records);
+            assert_eq!(encoded_size(&file), encode_file(&file).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn encoding_round_trips_8_33_khz_channels() {
+        // channel names in each 25 kHz block end in 00, 05, 10 and 15
+        let records = (118_000..137_000)
+            .filter(|khz| matches!(khz % 25, 0 | 5 | 10 | 15))
+            .map(|khz| {
+                let frequency = format!("{}.{:03}", khz / 1000, khz % 1000);
+                make_record("000001", &frequency, "", "", "", "", "")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 19 * 160);
+        assert_eq!(records.last().unwrap().frequency, "136.990");
+
+        let file = make_file(records);
+        let decoded = decode_file(&encode_file(&file).unwrap()).unwrap();
+        let decoded = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+        assert_eq!(decoded.unwrap(), file.records);
+    }
+
+    #[test]
+    fn single_records_round_trip() {
+        let record = make_record(
+            "3EE3C7",
+            "130.530",
+            "SG",
+            "Tobias Bieniek",
+            "EDKA",
+            "LS6a",
+            "D-0816",
+        );
+        let data = encode_record(&record).unwrap();
+        assert_eq!(&data[..4], &0x3EE3C7u32.to_le_bytes());
+        assert_eq!(crate::tdb::decode_record(&data).unwrap(), record);
+
+        let invalid = make_record("3EE3C7", "130,530", "", "", "", "", "");
+        assert_debug_snapshot!(encode_record(&invalid).unwrap_err(), @r###"
+        InvalidFrequency {
+            value: "130,530",
+            index: None,
+        }
+        "###);
+    }
 }
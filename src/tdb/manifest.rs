@@ -0,0 +1,90 @@
+use super::encode::{EncodeError, encode_file};
+use crate::File;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// Provenance metadata written next to a TDB file by [write_with_manifest].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub record_count: usize,
+    /// 64-bit FNV-1a hash of the encoded file, as 16 lowercase hex digits.
+    pub content_hash: String,
+    /// Size of the encoded file in bytes.
+    pub encoded_size: usize,
+}
+
+/// Encodes the file in TDB format, writes it to `path`, and writes a
+/// [Manifest] describing it to a sibling `.manifest.json` file.
+///
+/// For `flarmnet.tdb`, the manifest is written to `flarmnet.manifest.json`.
+pub fn write_with_manifest(path: &Path, file: &File) -> Result<Manifest, EncodeError> {
+    let data = encode_file(file)?;
+
+    let manifest = Manifest {
+        version: file.version,
+        record_count: file.records.len(),
+        content_hash: format!("{:016x}", fnv1a(&data)),
+        encoded_size: data.len(),
+    };
+
+    std::fs::write(path, &data)?;
+
+    let json = serde_json::to_vec_pretty(&manifest).map_err(io::Error::from)?;
+    std::fs::write(path.with_extension("manifest.json"), json)?;
+
+    Ok(manifest)
+}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Record;
+
+    #[test]
+    fn fnv1a_works() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn writing_with_manifest_works() {
+        let dir = std::env::temp_dir().join(format!("flarmnet-manifest-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("flarmnet.tdb");
+
+        let file = File {
+            version: 42,
+            records: vec![Record {
+                flarm_id: "3EE3C7".to_string(),
+                registration: "D-0816".to_string(),
+                ..Default::default()
+            }],
+        };
+
+        let manifest = write_with_manifest(&path, &file).unwrap();
+        assert_eq!(manifest.version, 42);
+        assert_eq!(manifest.record_count, 1);
+        assert_eq!(manifest.encoded_size, 12 + 4 + 8 + 96);
+
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(manifest.encoded_size, data.len());
+        assert_eq!(manifest.content_hash, format!("{:016x}", fnv1a(&data)));
+
+        let json = std::fs::read_to_string(dir.join("flarmnet.manifest.json")).unwrap();
+        let written: Manifest = serde_json::from_str(&json).unwrap();
+        assert_eq!(written, manifest);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
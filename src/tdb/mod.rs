@@ -5,7 +5,54 @@
 
 mod consts;
 mod decode;
+#[cfg(feature = "std")]
+mod decoded;
+#[cfg(feature = "std")]
+mod delta;
+#[cfg(feature = "std")]
 mod encode;
+#[cfg(feature = "serde")]
+mod manifest;
+mod options;
+#[cfg(feature = "std")]
+mod transform;
+#[cfg(feature = "std")]
+mod verify;
 
+#[cfg(feature = "std")]
+pub(crate) use consts::MAGIC;
+pub use consts::RECORD_SIZE;
 pub use decode::*;
+#[cfg(feature = "std")]
+pub use decoded::*;
+#[cfg(feature = "std")]
+pub use delta::*;
+#[cfg(feature = "std")]
 pub use encode::*;
+#[cfg(feature = "serde")]
+pub use manifest::*;
+pub use options::*;
+#[cfg(feature = "std")]
+pub use transform::*;
+#[cfg(feature = "std")]
+pub use verify::*;
+
+/// [FormatCodec](crate::FormatCodec) implementation for the TDB format,
+/// using [decode_file] and [encode_file].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Codec;
+
+#[cfg(feature = "std")]
+impl crate::FormatCodec for Codec {
+    type DecodeError = DecodeError;
+    type EncodeError = EncodeError;
+
+    fn decode(data: &[u8]) -> Result<crate::File, DecodeError> {
+        decode_file(data)?.try_into_file()
+    }
+
+    fn encode(file: &crate::File) -> Result<Vec<u8>, EncodeError> {
+        encode_file(file)
+    }
+}
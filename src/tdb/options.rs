@@ -0,0 +1,562 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Controls how a record without a frequency is represented on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyFrequency {
+    /// An empty frequency is stored as `0`, and `0` decodes to an empty
+    /// frequency.
+    #[default]
+    Zero,
+    /// An empty frequency is stored as the given value, and only that value
+    /// decodes to an empty frequency. A stored `0` then decodes to `0.000`.
+    Sentinel(u32),
+}
+
+#[cfg(feature = "std")]
+impl EmptyFrequency {
+    pub(crate) fn stored_value(self) -> u32 {
+        match self {
+            EmptyFrequency::Zero => 0,
+            EmptyFrequency::Sentinel(value) => value,
+        }
+    }
+}
+
+/// Controls how the unused bytes of text fields are filled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldPadding {
+    /// Unused bytes are filled with NUL bytes.
+    #[default]
+    Nul,
+    /// Unused bytes are filled with spaces, as expected by some legacy
+    /// readers. Decoding with this padding removes trailing spaces from text
+    /// fields.
+    Space,
+}
+
+/// Controls the case of the hex digits of decoded FLARM ids.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FlarmIdCase {
+    /// FLARM ids are decoded as e.g. `3EE3C7`.
+    #[default]
+    Upper,
+    /// FLARM ids are decoded as e.g. `3ee3c7`.
+    Lower,
+}
+
+/// Controls the order of records with the same FLARM id when encoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SecondarySort {
+    /// Records with the same FLARM id keep their input order.
+    #[default]
+    None,
+    /// Records with the same FLARM id are sorted by registration.
+    ByRegistration,
+}
+
+/// Controls how records with the same FLARM id are encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// All records are written, so the index contains the FLARM id more
+    /// than once.
+    #[default]
+    Keep,
+    /// Only the last record with the FLARM id is written.
+    Dedup,
+    /// Encoding fails with
+    /// [EncodeError::DuplicateFlarmId](super::EncodeError::DuplicateFlarmId).
+    Error,
+}
+
+/// Placeholder values that FlarmNet uses for unknown fields, see
+/// [DecodeOptions::normalize_placeholders].
+pub const DEFAULT_PLACEHOLDERS: [&str; 5] = ["UKN", "UNK", "UNKNOWN", "?", "-"];
+
+/// Options for [decode_file_with](super::decode_file_with).
+///
+/// The options can be constructed with [DecodeOptions::builder], which is
+/// unaffected by new options being added.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::tdb::{DecodeOptions, EmptyFrequency};
+/// let options = DecodeOptions::builder()
+///     .empty_frequency(EmptyFrequency::Sentinel(0xFFFFFFFF))
+///     .padding(16)
+///     .build();
+///
+/// assert_eq!(options.padding, Some(16));
+/// assert!(options.trust_header_count);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Stored frequency value that decodes to an empty frequency.
+    pub empty_frequency: EmptyFrequency,
+    /// Masks stored FLARM ids to their low 24 bits instead of rejecting ids
+    /// above `0xFFFFFF`. The remaining high bits are kept in
+    /// [Record::address_type](crate::Record::address_type).
+    pub mask_flarm_id: bool,
+    /// Size of the padding between the index and the record data. Defaults
+    /// to the standard 8 bytes. See [detect_padding](super::detect_padding)
+    /// for files that use a different padding size.
+    pub padding: Option<usize>,
+    /// Lowercases the text fields while decoding, e.g. for building a
+    /// case-insensitive search index. The FLARM id stays uppercase.
+    pub case_fold: bool,
+    /// Padding of the text fields. With [FieldPadding::Space], trailing
+    /// spaces are removed from text fields.
+    pub field_padding: FieldPadding,
+    /// Uses the record count from the header. Enabled by default.
+    ///
+    /// If disabled, the file is assumed to contain neither a valid record
+    /// count nor an index, and the record count is inferred from the file
+    /// size instead. This allows recovering records from files whose count
+    /// field was never written. [DecodedFile::index](super::DecodedFile::index)
+    /// is empty in that case.
+    pub trust_header_count: bool,
+    /// Returns the stored frequency as an integer number of kHz, e.g.
+    /// `"123500"`, instead of formatting it as MHz.
+    pub raw_frequency: bool,
+    /// Size of a record in bytes. Defaults to the standard 96 bytes, and
+    /// smaller sizes are rejected. See
+    /// [infer_record_size](super::infer_record_size) for files that use
+    /// larger records.
+    pub record_size: Option<usize>,
+    /// Rejects files with data after the last record with
+    /// [DecodeError::TrailingData](super::DecodeError::TrailingData), instead
+    /// of ignoring it.
+    pub exact_length: bool,
+    /// Decodes text fields that only contain whitespace or a placeholder
+    /// value as empty. Placeholders are compared case-insensitively, and
+    /// ignoring surrounding whitespace.
+    pub normalize_placeholders: bool,
+    /// Placeholder values used by [DecodeOptions::normalize_placeholders].
+    /// Defaults to [DEFAULT_PLACEHOLDERS].
+    pub placeholders: Option<Vec<String>>,
+    /// Checks that every index entry matches the FLARM id of its record and
+    /// that the index is sorted, and fails with
+    /// [DecodeError::IndexMismatch](super::DecodeError::IndexMismatch) or
+    /// [DecodeError::UnsortedIndex](super::DecodeError::UnsortedIndex)
+    /// otherwise.
+    pub verify_index: bool,
+    /// Decodes text fields with invalid UTF-8 by replacing the invalid bytes
+    /// with `U+FFFD`, instead of failing with
+    /// [DecodeError::InvalidUtf8](super::DecodeError::InvalidUtf8). See
+    /// [RecordOutcome::lossy_fields](super::RecordOutcome::lossy_fields) for
+    /// finding the affected fields.
+    pub lossy_utf8: bool,
+    /// Case of the hex digits of decoded FLARM ids. The encoder accepts both
+    /// cases.
+    pub flarm_id_case: FlarmIdCase,
+    /// Removes trailing spaces and ASCII control characters from text fields,
+    /// e.g. `\r` or the padding of writers that do not use NUL bytes.
+    /// Spaces within a field are kept.
+    pub trim_fields: bool,
+    /// Rejects the reserved FLARM id `000000` with
+    /// [DecodeError::InvalidFlarmId](super::DecodeError::InvalidFlarmId), e.g. for
+    /// finding placeholder records.
+    pub strict_flarm_id: bool,
+    /// Stores the position of every record in the file in
+    /// [Record::position](crate::Record::position).
+    pub record_positions: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            empty_frequency: EmptyFrequency::default(),
+            mask_flarm_id: false,
+            padding: None,
+            case_fold: false,
+            field_padding: FieldPadding::default(),
+            trust_header_count: true,
+            raw_frequency: false,
+            record_size: None,
+            exact_length: false,
+            normalize_placeholders: false,
+            placeholders: None,
+            verify_index: false,
+            lossy_utf8: false,
+            flarm_id_case: FlarmIdCase::default(),
+            trim_fields: false,
+            strict_flarm_id: false,
+            record_positions: false,
+        }
+    }
+}
+
+/// Options for [encode_file_with](super::encode_file_with) and
+/// [Writer::with_options](super::Writer::with_options).
+///
+/// The options can be constructed with [EncodeOptions::builder], which is
+/// unaffected by new options being added.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::tdb::EncodeOptions;
+/// let options = EncodeOptions::builder()
+///     .truncation_marker('~')
+///     .skip_invalid(true)
+///     .build();
+///
+/// assert_eq!(options.truncation_marker, Some('~'));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Stored frequency value that is written for an empty frequency.
+    pub empty_frequency: EmptyFrequency,
+    /// Masks FLARM ids to their low 24 bits instead of rejecting ids above
+    /// `0xFFFFFF`, and stores [Record::address_type](crate::Record::address_type)
+    /// in the high bits. Without it, records with an address type fail to
+    /// encode.
+    pub mask_flarm_id: bool,
+    /// Character that replaces the end of a text field that is too long to
+    /// be stored in full, so that truncated values are recognizable. The
+    /// marker is counted against the field's byte limit. By default, text
+    /// fields are truncated silently.
+    pub truncation_marker: Option<char>,
+    /// Rejects records with an empty FLARM id or registration with
+    /// [EncodeError::MissingField](super::EncodeError::MissingField).
+    /// Enabled by [EncodeOptions::strict].
+    pub require_ids: bool,
+    /// Padding that fills the unused bytes of text fields.
+    pub field_padding: FieldPadding,
+    /// Skips records with an invalid FLARM id instead of failing with
+    /// [EncodeError::InvalidFlarmId](super::EncodeError::InvalidFlarmId).
+    /// The positions of the skipped records are available from
+    /// [Writer::skipped](super::Writer::skipped).
+    pub skip_invalid: bool,
+    /// Expects frequencies as an integer number of kHz, e.g. `"123500"`, as
+    /// returned with [DecodeOptions::raw_frequency].
+    pub raw_frequency: bool,
+    /// Order of records with the same FLARM id. This does not affect
+    /// [Writer::write_sorted](super::Writer::write_sorted), which writes the
+    /// records in their input order.
+    pub secondary_sort: SecondarySort,
+    /// Handling of records with the same FLARM id. Only
+    /// [DuplicatePolicy::Error] applies to
+    /// [Writer::write_sorted](super::Writer::write_sorted).
+    pub on_duplicate: DuplicatePolicy,
+    /// Writes the records in their input order instead of sorting them by
+    /// FLARM id. The index always lists the FLARM ids in the order of the
+    /// records, since TDB readers locate a record by its position in the index,
+    /// so the index is unsorted as well and lookups that rely on a sorted index,
+    /// like [lookup](super::lookup), may not find records.
+    pub preserve_order: bool,
+    /// Rejects frequencies outside the 118.000–136.975 MHz aeronautical VHF
+    /// band, see [FrequencyBand](crate::FrequencyBand), with
+    /// [EncodeError::InvalidFrequency](super::EncodeError::InvalidFrequency).
+    /// Enabled by [EncodeOptions::strict].
+    pub require_airband: bool,
+    /// Rejects the reserved FLARM id `000000` with
+    /// [EncodeError::InvalidFlarmId](super::EncodeError::InvalidFlarmId).
+    /// Enabled by [EncodeOptions::strict].
+    pub strict_flarm_id: bool,
+    /// Maximum number of records, as supported by some devices. Files with
+    /// more records fail with [EncodeError::TooManyRecords](super::EncodeError::TooManyRecords).
+    /// Unlimited by default.
+    pub max_records: Option<usize>,
+    /// Fails with [EncodeError::FieldTooLong](super::EncodeError::FieldTooLong)
+    /// for text fields that do not fit into their 15 bytes, instead of
+    /// truncating them.
+    pub error_on_truncation: bool,
+}
+
+impl DecodeOptions {
+    /// Returns a builder that starts from the default options.
+    pub fn builder() -> DecodeOptionsBuilder {
+        DecodeOptionsBuilder::default()
+    }
+
+    pub(crate) fn is_placeholder(&self, value: &str) -> bool {
+        let value = value.trim();
+        if value.is_empty() {
+            return true;
+        }
+
+        let matches = |placeholder: &str| placeholder.eq_ignore_ascii_case(value);
+        match &self.placeholders {
+            Some(placeholders) => placeholders.iter().any(|it| matches(it)),
+            None => DEFAULT_PLACEHOLDERS.into_iter().any(matches),
+        }
+    }
+}
+
+/// Builder for [DecodeOptions], see [DecodeOptions::builder].
+#[derive(Debug, Clone, Default)]
+pub struct DecodeOptionsBuilder {
+    options: DecodeOptions,
+}
+
+impl DecodeOptionsBuilder {
+    /// Sets [DecodeOptions::empty_frequency].
+    pub fn empty_frequency(mut self, empty_frequency: EmptyFrequency) -> Self {
+        self.options.empty_frequency = empty_frequency;
+        self
+    }
+
+    /// Sets [DecodeOptions::mask_flarm_id].
+    pub fn mask_flarm_id(mut self, mask_flarm_id: bool) -> Self {
+        self.options.mask_flarm_id = mask_flarm_id;
+        self
+    }
+
+    /// Sets [DecodeOptions::padding].
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.options.padding = Some(padding);
+        self
+    }
+
+    /// Sets [DecodeOptions::case_fold].
+    pub fn case_fold(mut self, case_fold: bool) -> Self {
+        self.options.case_fold = case_fold;
+        self
+    }
+
+    /// Sets [DecodeOptions::field_padding].
+    pub fn field_padding(mut self, field_padding: FieldPadding) -> Self {
+        self.options.field_padding = field_padding;
+        self
+    }
+
+    /// Sets [DecodeOptions::trust_header_count].
+    pub fn trust_header_count(mut self, trust_header_count: bool) -> Self {
+        self.options.trust_header_count = trust_header_count;
+        self
+    }
+
+    /// Sets [DecodeOptions::raw_frequency].
+    pub fn raw_frequency(mut self, raw_frequency: bool) -> Self {
+        self.options.raw_frequency = raw_frequency;
+        self
+    }
+
+    /// Sets [DecodeOptions::record_size].
+    pub fn record_size(mut self, record_size: usize) -> Self {
+        self.options.record_size = Some(record_size);
+        self
+    }
+
+    /// Sets [DecodeOptions::exact_length].
+    pub fn exact_length(mut self, exact_length: bool) -> Self {
+        self.options.exact_length = exact_length;
+        self
+    }
+
+    /// Sets [DecodeOptions::normalize_placeholders].
+    pub fn normalize_placeholders(mut self, normalize_placeholders: bool) -> Self {
+        self.options.normalize_placeholders = normalize_placeholders;
+        self
+    }
+
+    /// Sets [DecodeOptions::placeholders].
+    pub fn placeholders<I, S>(mut self, placeholders: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.placeholders = Some(placeholders.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets [DecodeOptions::verify_index].
+    pub fn verify_index(mut self, verify_index: bool) -> Self {
+        self.options.verify_index = verify_index;
+        self
+    }
+
+    /// Sets [DecodeOptions::lossy_utf8].
+    pub fn lossy_utf8(mut self, lossy_utf8: bool) -> Self {
+        self.options.lossy_utf8 = lossy_utf8;
+        self
+    }
+
+    /// Sets [DecodeOptions::flarm_id_case].
+    pub fn flarm_id_case(mut self, flarm_id_case: FlarmIdCase) -> Self {
+        self.options.flarm_id_case = flarm_id_case;
+        self
+    }
+
+    /// Sets [DecodeOptions::trim_fields].
+    pub fn trim_fields(mut self, trim_fields: bool) -> Self {
+        self.options.trim_fields = trim_fields;
+        self
+    }
+
+    /// Sets [DecodeOptions::strict_flarm_id].
+    pub fn strict_flarm_id(mut self, strict_flarm_id: bool) -> Self {
+        self.options.strict_flarm_id = strict_flarm_id;
+        self
+    }
+
+    /// Sets [DecodeOptions::record_positions].
+    pub fn record_positions(mut self, record_positions: bool) -> Self {
+        self.options.record_positions = record_positions;
+        self
+    }
+
+    /// Returns the configured options.
+    pub fn build(self) -> DecodeOptions {
+        self.options
+    }
+}
+
+impl EncodeOptions {
+    /// Returns a builder that starts from the default options.
+    pub fn builder() -> EncodeOptionsBuilder {
+        EncodeOptionsBuilder::default()
+    }
+
+    /// Returns the default options with all checks enabled, which currently
+    /// means [EncodeOptions::require_ids], [EncodeOptions::require_airband]
+    /// and [EncodeOptions::strict_flarm_id].
+    pub fn strict() -> Self {
+        Self {
+            require_ids: true,
+            require_airband: true,
+            strict_flarm_id: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Builder for [EncodeOptions], see [EncodeOptions::builder].
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptionsBuilder {
+    options: EncodeOptions,
+}
+
+impl EncodeOptionsBuilder {
+    /// Sets [EncodeOptions::empty_frequency].
+    pub fn empty_frequency(mut self, empty_frequency: EmptyFrequency) -> Self {
+        self.options.empty_frequency = empty_frequency;
+        self
+    }
+
+    /// Sets [EncodeOptions::mask_flarm_id].
+    pub fn mask_flarm_id(mut self, mask_flarm_id: bool) -> Self {
+        self.options.mask_flarm_id = mask_flarm_id;
+        self
+    }
+
+    /// Sets [EncodeOptions::truncation_marker].
+    pub fn truncation_marker(mut self, truncation_marker: char) -> Self {
+        self.options.truncation_marker = Some(truncation_marker);
+        self
+    }
+
+    /// Sets [EncodeOptions::require_ids].
+    pub fn require_ids(mut self, require_ids: bool) -> Self {
+        self.options.require_ids = require_ids;
+        self
+    }
+
+    /// Sets [EncodeOptions::field_padding].
+    pub fn field_padding(mut self, field_padding: FieldPadding) -> Self {
+        self.options.field_padding = field_padding;
+        self
+    }
+
+    /// Sets [EncodeOptions::skip_invalid].
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.options.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Sets [EncodeOptions::raw_frequency].
+    pub fn raw_frequency(mut self, raw_frequency: bool) -> Self {
+        self.options.raw_frequency = raw_frequency;
+        self
+    }
+
+    /// Sets [EncodeOptions::secondary_sort].
+    pub fn secondary_sort(mut self, secondary_sort: SecondarySort) -> Self {
+        self.options.secondary_sort = secondary_sort;
+        self
+    }
+
+    /// Sets [EncodeOptions::on_duplicate].
+    pub fn on_duplicate(mut self, on_duplicate: DuplicatePolicy) -> Self {
+        self.options.on_duplicate = on_duplicate;
+        self
+    }
+
+    /// Sets [EncodeOptions::preserve_order].
+    pub fn preserve_order(mut self, preserve_order: bool) -> Self {
+        self.options.preserve_order = preserve_order;
+        self
+    }
+
+    /// Sets [EncodeOptions::require_airband].
+    pub fn require_airband(mut self, require_airband: bool) -> Self {
+        self.options.require_airband = require_airband;
+        self
+    }
+
+    /// Sets [EncodeOptions::strict_flarm_id].
+    pub fn strict_flarm_id(mut self, strict_flarm_id: bool) -> Self {
+        self.options.strict_flarm_id = strict_flarm_id;
+        self
+    }
+
+    /// Sets [EncodeOptions::max_records].
+    pub fn max_records(mut self, max_records: usize) -> Self {
+        self.options.max_records = Some(max_records);
+        self
+    }
+
+    /// Sets [EncodeOptions::error_on_truncation].
+    pub fn error_on_truncation(mut self, error_on_truncation: bool) -> Self {
+        self.options.error_on_truncation = error_on_truncation;
+        self
+    }
+
+    /// Returns the configured options.
+    pub fn build(self) -> EncodeOptions {
+        self.options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_match_struct_literals() {
+        let options = DecodeOptions::builder()
+            .mask_flarm_id(true)
+            .trust_header_count(false)
+            .record_size(128)
+            .build();
+        let expected = DecodeOptions {
+            mask_flarm_id: true,
+            trust_header_count: false,
+            record_size: Some(128),
+            ..Default::default()
+        };
+        assert_eq!(options, expected);
+        assert_eq!(DecodeOptions::builder().build(), DecodeOptions::default());
+
+        let options = EncodeOptions::builder()
+            .require_ids(true)
+            .field_padding(FieldPadding::Space)
+            .build();
+        let expected = EncodeOptions {
+            require_ids: true,
+            field_padding: FieldPadding::Space,
+            ..Default::default()
+        };
+        assert_eq!(options, expected);
+        assert_eq!(
+            EncodeOptions::builder()
+                .require_ids(true)
+                .require_airband(true)
+                .strict_flarm_id(true)
+                .build(),
+            EncodeOptions::strict()
+        );
+    }
+}
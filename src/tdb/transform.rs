@@ -0,0 +1,91 @@
+use super::{DecodeError, EncodeError, decode_file, encode_file};
+use crate::{File, Record};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TransformError {
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+}
+
+/// Decodes a file, passes every record through `f`, and encodes the result
+/// again.
+///
+/// Records for which `f` returns `None` are dropped, as are records that
+/// failed to decode. The version of the file is kept.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![
+///         Record { flarm_id: "3EE3C7".to_string(), pilot_name: "Tobias Bieniek".to_string(), ..Default::default() },
+///         Record { flarm_id: "000001".to_string(), ..Default::default() },
+///     ],
+/// };
+/// let data = flarmnet::tdb::encode_file(&file).unwrap();
+///
+/// let data = flarmnet::tdb::transform(&data, |mut record| {
+///     record.anonymize();
+///     (record.flarm_id != "000001").then_some(record)
+/// })
+/// .unwrap();
+///
+/// let decoded = flarmnet::tdb::decode_file(&data).unwrap();
+/// assert_eq!(decoded.records.len(), 1);
+/// assert_eq!(decoded.records[0].as_ref().unwrap().pilot_name, "");
+/// ```
+pub fn transform(
+    data: &[u8],
+    f: impl FnMut(Record) -> Option<Record>,
+) -> Result<Vec<u8>, TransformError> {
+    let decoded = decode_file(data)?;
+
+    let file = File {
+        version: decoded.version,
+        records: decoded
+            .records
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter_map(f)
+            .collect(),
+    };
+
+    Ok(encode_file(&file)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforming_fails_for_invalid_records() {
+        let file = File {
+            version: 1,
+            records: vec![Record {
+                flarm_id: "000001".to_string(),
+                ..Default::default()
+            }],
+        };
+        let data = encode_file(&file).unwrap();
+
+        let result = transform(&data, |record| {
+            Some(Record {
+                flarm_id: "XYZ".to_string(),
+                ..record
+            })
+        });
+        assert!(matches!(
+            result,
+            Err(TransformError::Encode(EncodeError::InvalidFlarmId { .. }))
+        ));
+        assert!(matches!(
+            transform(b"", Some),
+            Err(TransformError::Decode(DecodeError::UnexpectedEof))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use super::{DecodeError, DecodeOptions, EncodeError, EncodeOptions};
+use super::{decode_file_with, encode_file_with};
+use crate::{File, Record};
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    #[error("record {index} failed to decode: {source}")]
+    InvalidRecord { index: usize, source: DecodeError },
+}
+
+/// Encodes a file with the default options and checks that every record of
+/// the result decodes again.
+///
+/// Fails with [VerifyError::InvalidRecord] for the first record that does
+/// not decode, which would indicate a bug in the encoder. Unlike
+/// [verify_roundtrip], records that decode with different values are not
+/// reported.
+pub fn encode_and_verify(file: &File) -> Result<Vec<u8>, VerifyError> {
+    let encoded = encode_file_with(file, &EncodeOptions::default())?;
+    let decoded = decode_file_with(&encoded, &DecodeOptions::default())?;
+
+    for (index, result) in decoded.records.into_iter().enumerate() {
+        if let Err(source) = result {
+            return Err(VerifyError::InvalidRecord { index, source });
+        }
+    }
+
+    Ok(encoded)
+}
+
+/// Encodes and decodes a file with the default options and returns the
+/// indices of the records that did not survive unchanged.
+///
+/// See [verify_roundtrip_with] for details.
+pub fn verify_roundtrip(file: &File) -> Result<Vec<usize>, VerifyError> {
+    verify_roundtrip_with(file, &EncodeOptions::default(), &DecodeOptions::default())
+}
+
+/// Encodes a file with the given [EncodeOptions], decodes the result with
+/// the given [DecodeOptions], and returns the indices of the records in
+/// `file.records` that did not survive unchanged.
+///
+/// Decoded records are matched to the original records by FLARM id
+/// (case-insensitively, and in their original order for duplicate ids).
+/// Records that were truncated, reformatted, or failed to decode are
+/// reported.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::{File, Record};
+/// let file = File {
+///     version: 1,
+///     records: vec![
+///         Record { flarm_id: "3EE3C7".to_string(), frequency: "123.500".to_string(), ..Default::default() },
+///         Record { flarm_id: "000001".to_string(), frequency: "123.5".to_string(), ..Default::default() },
+///     ],
+/// };
+///
+/// assert_eq!(flarmnet::tdb::verify_roundtrip(&file).unwrap(), vec![1]);
+/// ```
+pub fn verify_roundtrip_with(
+    file: &File,
+    encode_options: &EncodeOptions,
+    decode_options: &DecodeOptions,
+) -> Result<Vec<usize>, VerifyError> {
+    let encoded = encode_file_with(file, encode_options)?;
+    let decoded = decode_file_with(&encoded, decode_options)?;
+
+    let mut decoded_by_id: HashMap<String, VecDeque<Option<Record>>> = HashMap::new();
+    for (i, result) in decoded.records.into_iter().enumerate() {
+        let id = match &result {
+            Ok(record) => record.flarm_id.to_ascii_uppercase(),
+            Err(_) => match decoded.index.get(i) {
+                Some(id) => format!("{id:06X}"),
+                None => continue,
+            },
+        };
+        decoded_by_id.entry(id).or_default().push_back(result.ok());
+    }
+
+    let mismatches = file
+        .records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| {
+            let id = record.flarm_id.to_ascii_uppercase();
+            let decoded = decoded_by_id.get_mut(&id).and_then(VecDeque::pop_front);
+            decoded.flatten().as_ref() != Some(*record)
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tdb::EmptyFrequency;
+
+    fn make_record(flarm_id: &str, frequency: &str, call_sign: &str) -> Record {
+        Record {
+            flarm_id: flarm_id.to_string(),
+            frequency: frequency.to_string(),
+            call_sign: call_sign.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn verifying_roundtrip_reports_changed_records() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("00000F", "123.500", "X27"),
+                make_record("000001", "", "0123456789ABCDEF"),
+                make_record("000001", "", "SG"),
+                make_record("c0ffee", "", ""),
+            ],
+        };
+        assert_eq!(verify_roundtrip(&file).unwrap(), vec![1, 3]);
+    }
+
+    #[test]
+    fn verifying_roundtrip_uses_the_given_options() {
+        let file = File {
+            version: 1,
+            records: vec![make_record("000001", "0.000", "")],
+        };
+        let encode_options = EncodeOptions {
+            empty_frequency: EmptyFrequency::Sentinel(0xFFFFFFFF),
+            ..Default::default()
+        };
+
+        let result = verify_roundtrip_with(&file, &encode_options, &DecodeOptions::default());
+        assert_eq!(result.unwrap(), vec![0]);
+
+        let decode_options = DecodeOptions {
+            empty_frequency: EmptyFrequency::Sentinel(0xFFFFFFFF),
+            ..Default::default()
+        };
+        let result = verify_roundtrip_with(&file, &encode_options, &decode_options);
+        assert_eq!(result.unwrap(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn verifying_roundtrip_fails_for_invalid_records() {
+        let file = File {
+            version: 1,
+            records: vec![make_record("ZZZZZZ", "", "")],
+        };
+        assert!(matches!(
+            verify_roundtrip(&file),
+            Err(VerifyError::Encode(EncodeError::InvalidFlarmId { .. }))
+        ));
+    }
+
+    #[test]
+    fn encoding_and_verifying_works() {
+        let file = File {
+            version: 1,
+            records: vec![
+                make_record("00000F", "123.500", "X27"),
+                make_record("000001", "", "01234567890123Ä"),
+            ],
+        };
+        let encoded = encode_and_verify(&file).unwrap();
+        assert_eq!(encoded, crate::tdb::encode_file(&file).unwrap());
+
+        let file = File {
+            version: 1,
+            records: vec![make_record("ZZZZZZ", "", "")],
+        };
+        assert!(matches!(
+            encode_and_verify(&file),
+            Err(VerifyError::Encode(EncodeError::InvalidFlarmId { .. }))
+        ));
+    }
+}
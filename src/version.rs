@@ -0,0 +1,54 @@
+use std::path::Path;
+
+/// Extracts a `YYYYMMDD` date from a file name like `data_20240115.fln`, for
+/// cross-checking it against the version stored in the file.
+///
+/// Only runs of exactly eight digits that form a plausible date are
+/// considered. If there are several, the last one is used. Directory
+/// components of `name` are ignored.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(flarmnet::version_from_filename("data_20240115.fln"), Some(20240115));
+/// assert_eq!(flarmnet::version_from_filename("2023/data_v2.fln"), None);
+/// assert_eq!(flarmnet::version_from_filename("data_20241315.fln"), None);
+/// ```
+pub fn version_from_filename(name: &str) -> Option<u32> {
+    let name = Path::new(name).file_name()?.to_str()?;
+
+    name.split(|c: char| !c.is_ascii_digit())
+        .filter(|digits| digits.len() == 8)
+        .filter_map(|digits| digits.parse::<u32>().ok())
+        .filter(|date| is_plausible_date(*date))
+        .next_back()
+}
+
+fn is_plausible_date(date: u32) -> bool {
+    let year = date / 10000;
+    let month = date / 100 % 100;
+    let day = date % 100;
+
+    (1900..=2999).contains(&year) && (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::version_from_filename;
+
+    #[test]
+    fn version_from_filename_works() {
+        assert_eq!(version_from_filename("20240115.tdb"), Some(20240115));
+        assert_eq!(
+            version_from_filename("/tmp/20230101/data_20240115.fln"),
+            Some(20240115)
+        );
+        assert_eq!(
+            version_from_filename("data_20240115_20240201.fln"),
+            Some(20240201)
+        );
+        assert_eq!(version_from_filename("data_202401150.fln"), None);
+        assert_eq!(version_from_filename("data_20240100.fln"), None);
+        assert_eq!(version_from_filename(""), None);
+    }
+}
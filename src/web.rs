@@ -0,0 +1,172 @@
+//! Importer for the JSON shape used by the FlarmNet web API.
+//!
+//! The API uses its own field names, which are mapped onto [Record] by the
+//! [from_json] function:
+//!
+//! ```json
+//! {
+//!   "version": 28592,
+//!   "data": [
+//!     {
+//!       "id": "3EE3C7",
+//!       "name": "Tobias Bieniek",
+//!       "airfield": "EDKA",
+//!       "type": "LS6a",
+//!       "reg": "D-0816",
+//!       "cn": "SG",
+//!       "freq": "130.530"
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Missing fields default to empty strings, and a missing `version` defaults
+//! to `0`.
+
+use crate::{File, Record};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct WebFile {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    data: Vec<WebRecord>,
+}
+
+#[derive(Deserialize)]
+struct WebRecord {
+    #[serde(rename = "id")]
+    flarm_id: String,
+    #[serde(rename = "name", default)]
+    pilot_name: String,
+    #[serde(rename = "airfield", default)]
+    airfield: String,
+    #[serde(rename = "type", default)]
+    plane_type: String,
+    #[serde(rename = "reg", default)]
+    registration: String,
+    #[serde(rename = "cn", default)]
+    call_sign: String,
+    #[serde(rename = "freq", default)]
+    frequency: String,
+}
+
+impl From<WebRecord> for Record {
+    fn from(record: WebRecord) -> Self {
+        Record {
+            flarm_id: record.flarm_id,
+            pilot_name: record.pilot_name,
+            airfield: record.airfield,
+            plane_type: record.plane_type,
+            registration: record.registration,
+            call_sign: record.call_sign,
+            frequency: record.frequency,
+            ..Default::default()
+        }
+    }
+}
+
+/// Converts FlarmNet web API JSON into a [File].
+///
+/// # Examples
+///
+/// ```
+/// let json = r#"{"version": 123, "data": [{"id": "3EE3C7", "reg": "D-0816", "cn": "SG"}]}"#;
+///
+/// let file = flarmnet::web::from_json(json).unwrap();
+/// assert_eq!(file.version, 123);
+/// assert_eq!(file.records[0].call_sign, "SG");
+/// ```
+pub fn from_json(json: &str) -> Result<File, serde_json::Error> {
+    let file: WebFile = serde_json::from_str(json)?;
+
+    Ok(File {
+        version: file.version,
+        records: file.data.into_iter().map(Record::from).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_json;
+    use insta::assert_debug_snapshot;
+
+    #[test]
+    fn importing_works() {
+        let json = r#"{
+            "version": 28592,
+            "data": [
+                {
+                    "id": "3EE3C7",
+                    "name": "Tobias Bieniek",
+                    "airfield": "EDKA",
+                    "type": "LS6a",
+                    "reg": "D-0816",
+                    "cn": "SG",
+                    "freq": "130.530"
+                },
+                { "id": "000001", "type": "Paraglider" }
+            ]
+        }"#;
+
+        assert_debug_snapshot!(from_json(json).unwrap(), @r###"
+        File {
+            version: 28592,
+            records: [
+                Record {
+                    flarm_id: "3EE3C7",
+                    pilot_name: "Tobias Bieniek",
+                    airfield: "EDKA",
+                    plane_type: "LS6a",
+                    registration: "D-0816",
+                    call_sign: "SG",
+                    frequency: "130.530",
+                    address_type: 0,
+                    reserved: [
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                    position: None,
+                },
+                Record {
+                    flarm_id: "000001",
+                    pilot_name: "",
+                    airfield: "",
+                    plane_type: "Paraglider",
+                    registration: "",
+                    call_sign: "",
+                    frequency: "",
+                    address_type: 0,
+                    reserved: [
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                    position: None,
+                },
+            ],
+        }
+        "###);
+    }
+
+    #[test]
+    fn importing_fails_for_missing_flarm_id() {
+        let json = r#"{"data": [{"reg": "D-0816"}]}"#;
+        assert_eq!(
+            from_json(json).unwrap_err().to_string(),
+            "missing field `id` at line 1 column 27"
+        );
+    }
+}
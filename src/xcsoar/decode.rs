@@ -3,7 +3,7 @@ use crate::Record;
 use encoding_rs::mem::decode_latin1;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum DecodeError {
     #[error("missing file version")]
     MissingVersion,
@@ -17,7 +17,7 @@ pub enum DecodeError {
     InvalidFlarmId(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DecodedFile {
     pub version: u32,
     pub records: Vec<Result<Record, DecodeError>>,
@@ -25,6 +25,10 @@ pub struct DecodedFile {
 
 /// Decodes a FlarmNet file.
 ///
+/// A leading UTF-8 byte order mark is ignored, and lines may end with
+/// either LF or CRLF. Use [detect_issues](super::detect_issues) to find
+/// such problems without decoding the file.
+///
 /// # Examples
 ///
 /// ```
@@ -40,6 +44,7 @@ pub struct DecodedFile {
 /// assert_eq!(result.records.iter().filter(|it| it.is_ok()).count(), 3);
 /// ```
 pub fn decode_file(file: &str) -> Result<DecodedFile, DecodeError> {
+    let file = file.strip_prefix('\u{FEFF}').unwrap_or(file);
     let mut lines = file.lines();
 
     let version = lines.next().ok_or(DecodeError::MissingVersion)?;
@@ -49,7 +54,10 @@ pub fn decode_file(file: &str) -> Result<DecodedFile, DecodeError> {
     let records = lines
         .filter(|it| !it.is_empty())
         .map(decode_record)
-        .collect();
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "log")]
+    crate::logging::log_records("XCSoar", version, &records);
 
     Ok(DecodedFile { version, records })
 }
@@ -71,6 +79,7 @@ pub fn decode_file(file: &str) -> Result<DecodedFile, DecodeError> {
 ///     registration: "D-0816".to_string(),
 ///     call_sign: "SG".to_string(),
 ///     frequency: "130.530".to_string(),
+///     ..Default::default()
 /// });
 /// ```
 pub fn decode_record(line: &str) -> Result<Record, DecodeError> {
@@ -99,6 +108,7 @@ pub fn decode_record(line: &str) -> Result<Record, DecodeError> {
         registration,
         call_sign,
         frequency,
+        ..Default::default()
     })
 }
 
@@ -138,6 +148,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decoding_ignores_bom_and_crlf() {
+        let file = "\u{FEFF}00007b\r\n334545334337546f62696173204269656e69656b2020202020202045444b4120202020202020202020202020202020204c5336612020202020202020202020202020202020442d30383136205347203133302e353330\r\n";
+
+        let result = decode_file(file).unwrap();
+        assert_eq!(result.version, 123);
+        assert_eq!(result.records[0].as_ref().unwrap().flarm_id, "3EE3C7");
+    }
+
     #[test]
     fn decoding_fails_for_short_line() {
         let row = "3030303030304dfc6c6c6572202020202020202020202020202020442d3231383820202020202020202020202020202041534b2d3133202020202020202020202020202020442d32313838202020203132332e31353";
@@ -30,6 +30,7 @@ pub enum EncodeError {
 ///             registration: "D-0816".to_string(),
 ///             call_sign: "SG".to_string(),
 ///             frequency: "130.530".to_string(),
+///             ..Default::default()
 ///         }
 ///     ]
 /// };
@@ -48,7 +49,38 @@ pub fn encode_file(file: &File) -> Result<Vec<u8>, EncodeError> {
     Ok(buffer)
 }
 
-#[derive(Clone)]
+/// Encodes a single FlarmNet file record as a line of hex-encoded
+/// characters, without the trailing newline.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::Record;
+/// let record = Record {
+///     flarm_id: "3EE3C7".to_string(),
+///     pilot_name: "Tobias Bieniek".to_string(),
+///     airfield: "EDKA".to_string(),
+///     plane_type: "LS6a".to_string(),
+///     registration: "D-0816".to_string(),
+///     call_sign: "SG".to_string(),
+///     frequency: "130.530".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let line = flarmnet::xcsoar::encode_record(&record).unwrap();
+/// assert_eq!(flarmnet::xcsoar::decode_record(&line).unwrap(), record);
+/// ```
+pub fn encode_record(record: &Record) -> Result<String, EncodeError> {
+    let mut writer = Writer::new(Vec::with_capacity(LINE_LENGTH));
+    writer.write_fields(record)?;
+
+    let buffer = writer.into_inner();
+
+    // the output only consists of hex digits
+    Ok(String::from_utf8(buffer).unwrap())
+}
+
+#[derive(Debug, Clone)]
 pub struct Writer<W: Write> {
     writer: W,
 }
@@ -75,6 +107,13 @@ impl<W: Write> Writer<W> {
     }
 
     fn write_record(&mut self, record: &Record) -> Result<(), EncodeError> {
+        self.write_fields(record)?;
+        self.writer.write_all(b"\n")?;
+
+        Ok(())
+    }
+
+    fn write_fields(&mut self, record: &Record) -> Result<(), EncodeError> {
         self.write_str(&record.flarm_id, FLARM_ID_LENGTH)?;
         self.write_str(&record.pilot_name, PILOT_NAME_LENGTH)?;
         self.write_str(&record.airfield, AIRFIELD_LENGTH)?;
@@ -82,7 +121,6 @@ impl<W: Write> Writer<W> {
         self.write_str(&record.registration, REGISTRATION_LENGTH)?;
         self.write_str(&record.call_sign, CALL_SIGN_LENGTH)?;
         self.write_str(&record.frequency, FREQUENCY_LENGTH)?;
-        self.writer.write_all(b"\n")?;
 
         Ok(())
     }
@@ -113,7 +151,8 @@ impl<W: Write> Writer<W> {
 
 #[cfg(test)]
 mod tests {
-    use super::{EncodeError, Writer};
+    use super::{EncodeError, Writer, encode_record};
+    use crate::Record;
     use insta::assert_debug_snapshot;
     use std::io::Cursor;
 
@@ -141,4 +180,19 @@ mod tests {
             )
             "###);
     }
+
+    #[test]
+    fn encoding_record_fails_for_non_latin1() {
+        let record = Record {
+            pilot_name: "😅".to_string(),
+            ..Default::default()
+        };
+        assert_debug_snapshot!(
+            encode_record(&record).unwrap_err(),
+            @r###"
+            InvalidEncoding(
+                "😅",
+            )
+            "###);
+    }
 }
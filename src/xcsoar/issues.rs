@@ -0,0 +1,99 @@
+/// A problem with the raw data of a FlarmNet file, see [detect_issues].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlnIssue {
+    /// The file starts with a UTF-8 byte order mark.
+    ByteOrderMark,
+    /// The file uses both LF and CRLF line endings.
+    MixedLineEndings,
+    /// The line with the given 1-based number contains characters other
+    /// than hex digits.
+    NonHexCharacters { line: usize },
+}
+
+/// Checks the raw data of a FlarmNet file for problems that commonly occur
+/// in downloaded files, so that users can be warned before decoding.
+///
+/// A byte order mark and CRLF line endings are handled by
+/// [decode_file](super::decode_file), but usually point to a file that was
+/// modified by another tool.
+///
+/// # Examples
+///
+/// ```
+/// # use flarmnet::xcsoar::{FlnIssue, detect_issues};
+/// let data = b"\xEF\xBB\xBF00007b\r\n3030303030x0\n";
+///
+/// assert_eq!(detect_issues(data), vec![
+///     FlnIssue::ByteOrderMark,
+///     FlnIssue::MixedLineEndings,
+///     FlnIssue::NonHexCharacters { line: 2 },
+/// ]);
+/// ```
+pub fn detect_issues(data: &[u8]) -> Vec<FlnIssue> {
+    let mut issues = Vec::new();
+
+    let data = match data.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(data) => {
+            issues.push(FlnIssue::ByteOrderMark);
+            data
+        }
+        None => data,
+    };
+
+    let mut lf = false;
+    let mut crlf = false;
+    let mut non_hex = Vec::new();
+    let mut lines = data.split(|&byte| byte == b'\n').peekable();
+    let mut number = 0;
+    while let Some(line) = lines.next() {
+        number += 1;
+        let is_last = lines.peek().is_none();
+
+        let line = match line.strip_suffix(b"\r") {
+            Some(line) if !is_last => {
+                crlf = true;
+                line
+            }
+            _ => {
+                lf |= !is_last;
+                line
+            }
+        };
+
+        if !line.iter().all(u8::is_ascii_hexdigit) {
+            non_hex.push(FlnIssue::NonHexCharacters { line: number });
+        }
+    }
+
+    if lf && crlf {
+        issues.push(FlnIssue::MixedLineEndings);
+    }
+    issues.extend(non_hex);
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_issues_accepts_valid_files() {
+        assert_eq!(detect_issues(b""), vec![]);
+        assert_eq!(detect_issues(b"00007b\n303030303030\n"), vec![]);
+        assert_eq!(detect_issues(b"00007b\r\n303030303030\r\n"), vec![]);
+        assert_eq!(detect_issues(b"00007b\n303030303030"), vec![]);
+    }
+
+    #[test]
+    fn detect_issues_reports_stray_characters() {
+        assert_eq!(
+            detect_issues(b"00007b\n30303030 3030\n\r\n\xFC"),
+            vec![
+                FlnIssue::MixedLineEndings,
+                FlnIssue::NonHexCharacters { line: 2 },
+                FlnIssue::NonHexCharacters { line: 4 },
+            ]
+        );
+    }
+}
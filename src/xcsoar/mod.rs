@@ -2,10 +2,55 @@
 //!
 //! The [decode_file] function can be used to decode FlarmNet files. The
 //! [encode_file] function can be used to write such files.
+//!
+//! This is the classic FlarmNet `.fln` distribution format. The first line
+//! holds the version as a hex number, and every following line holds one
+//! record as the hex-encoded Latin-1 bytes of its fixed-width, space-padded
+//! fields. Records decode into the same [Record](crate::Record) type as the
+//! other formats, so a file can be converted directly:
+//!
+//! ```
+//! let fln = include_str!("../../tests/fixtures/data.fln");
+//!
+//! let decoded = flarmnet::xcsoar::decode_file(fln).unwrap();
+//! let file = flarmnet::File {
+//!     version: decoded.version,
+//!     records: decoded.records.into_iter().filter_map(Result::ok).collect(),
+//! };
+//! let tdb = flarmnet::tdb::encode_file(&file).unwrap();
+//! assert_eq!(flarmnet::tdb::decode_file(&tdb).unwrap().version, 28592);
+//! ```
 
 mod decode;
 mod encode;
-mod fields;
+pub(crate) mod fields;
+mod issues;
 
 pub use decode::*;
 pub use encode::*;
+pub use issues::*;
+
+/// [FormatCodec](crate::FormatCodec) implementation for the XCSoar format,
+/// using [decode_file] and [encode_file].
+///
+/// Invalid UTF-8 is replaced before decoding, and then fails like any other
+/// unexpected character.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Codec;
+
+impl crate::FormatCodec for Codec {
+    type DecodeError = DecodeError;
+    type EncodeError = EncodeError;
+
+    fn decode(data: &[u8]) -> Result<crate::File, DecodeError> {
+        let decoded = decode_file(&String::from_utf8_lossy(data))?;
+        Ok(crate::File {
+            version: decoded.version,
+            records: decoded.records.into_iter().collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn encode(file: &crate::File) -> Result<Vec<u8>, EncodeError> {
+        encode_file(file)
+    }
+}
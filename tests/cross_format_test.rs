@@ -0,0 +1,26 @@
+use flarmnet::{File, tdb, xcsoar};
+
+#[test]
+fn tdb_to_xcsoar_to_tdb() {
+    let fixture = include_bytes!("fixtures/flarmnet.tdb");
+    let decoded = tdb::decode_file(fixture).unwrap();
+    let file = File {
+        version: decoded.version,
+        records: decoded
+            .records
+            .into_iter()
+            .filter_map(|it| it.ok())
+            .collect(),
+    };
+
+    let fln = xcsoar::encode_file(&file).unwrap();
+    let decoded = xcsoar::decode_file(std::str::from_utf8(&fln).unwrap()).unwrap();
+    let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+    let converted = File {
+        version: decoded.version,
+        records: records.unwrap(),
+    };
+    assert_eq!(converted, file);
+
+    assert_eq!(tdb::encode_file(&converted).unwrap(), fixture.as_ref());
+}
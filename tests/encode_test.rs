@@ -12,5 +12,11 @@ fn it_works() {
             .filter_map(|it| it.ok())
             .collect(),
     };
-    assert_eq!(encode_file(&file).unwrap(), fixture.as_bytes());
+    let encoded = encode_file(&file).unwrap();
+    assert_eq!(encoded, fixture.as_bytes());
+
+    let decoded = decode_file(std::str::from_utf8(&encoded).unwrap()).unwrap();
+    assert_eq!(decoded.version, file.version);
+    let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+    assert_eq!(records.unwrap(), file.records);
 }
@@ -12,5 +12,11 @@ fn it_works() {
             .filter_map(|it| it.ok())
             .collect(),
     };
-    assert_eq!(encode_file(&file).unwrap(), fixture.as_ref());
+    let encoded = encode_file(&file).unwrap();
+    assert_eq!(encoded, fixture.as_ref());
+
+    let decoded = decode_file(&encoded).unwrap();
+    assert_eq!(decoded.version, file.version);
+    let records = decoded.records.into_iter().collect::<Result<Vec<_>, _>>();
+    assert_eq!(records.unwrap(), file.records);
 }